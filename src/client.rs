@@ -1,93 +1,199 @@
-#[derive(Clone, Default)]
-pub struct Client(reqwest::Client);
+pub mod blocking;
+#[cfg(any(feature = "client", feature = "client-blocking"))]
+mod builder;
+mod flow;
+pub mod transport;
+
+#[cfg(any(feature = "client", feature = "client-blocking"))]
+pub use builder::Builder;
+pub use transport::Transport;
+
+#[derive(Clone, Debug)]
+pub struct Client(std::sync::Arc<dyn Transport>);
+
+/// Transport a flow runs on, either borrowed from its client or shared with it.
+enum Handle<'a, T: ?Sized> {
+    Borrowed(&'a std::sync::Arc<T>),
+    Owned(std::sync::Arc<T>),
+}
+
+impl<T: ?Sized> Handle<'_, T> {
+    fn into_owned(self) -> Handle<'static, T> {
+        match self {
+            Handle::Borrowed(t) => Handle::Owned(t.clone()),
+            Handle::Owned(t) => Handle::Owned(t),
+        }
+    }
+}
+
+impl<T: ?Sized> Clone for Handle<'_, T> {
+    fn clone(&self) -> Self {
+        match self {
+            Handle::Borrowed(t) => Handle::Borrowed(t),
+            Handle::Owned(t) => Handle::Owned(t.clone()),
+        }
+    }
+}
+
+impl<T: ?Sized> std::ops::Deref for Handle<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            Handle::Borrowed(t) => t,
+            Handle::Owned(t) => t,
+        }
+    }
+}
+
+impl<T: ?Sized + std::fmt::Debug> std::fmt::Debug for Handle<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[cfg(feature = "client")]
+impl Default for Client {
+    fn default() -> Self {
+        Builder::default().build().expect("default client")
+    }
+}
 
 impl Client {
+    /// Settings for a `reqwest` backed client, see [`Builder::build`].
+    #[cfg(feature = "client")]
+    #[must_use]
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    /// Client running its flows on `transport`.
+    pub fn new(transport: impl Transport + 'static) -> Self {
+        Client(std::sync::Arc::new(transport))
+    }
+
     /// # Errors
     ///
-    /// Returns errors on network or deserialization failures.
-    pub async fn entrypoint(&self, s: &str) -> Result<Entrypoint, &'static str> {
-        let client = &self.0;
-
-        let url = match crate::resolve(s)? {
-            crate::Resolved::Url(url) => url,
-            crate::Resolved::Auth(_, core) => return Ok(Entrypoint::Auth(Auth { client, core })),
-            crate::Resolved::Withdraw(_, core) => {
-                return Ok(Entrypoint::Withdraw(Withdraw { client, core }))
-            }
-        };
-
-        let response = client.get(url).send().await.map_err(|_| "request failed")?;
-        let bytes = response.bytes().await.map_err(|_| "body failed")?;
-
-        (&bytes as &[u8])
-            .try_into()
-            .map_err(|_| "parse failed")
-            .map(|query: crate::Entrypoint| match query {
-                crate::Entrypoint::Channel(core) => Entrypoint::Channel(Channel { client, core }),
-                crate::Entrypoint::Pay(core) => Entrypoint::Pay(Pay { client, core }),
-                crate::Entrypoint::Withdraw(core) => {
-                    Entrypoint::Withdraw(Withdraw { client, core })
-                }
-            })
+    /// Returns errors on network or deserialization failures, or on LNURL `ERROR` responses.
+    pub async fn entrypoint(&self, s: &str) -> Result<Entrypoint<'_>, Error> {
+        entrypoint(Handle::Borrowed(&self.0), s).await
     }
 }
 
+async fn entrypoint<'a>(
+    client: Handle<'a, dyn Transport>,
+    s: &str,
+) -> Result<Entrypoint<'a>, Error> {
+    let url = match crate::resolve(s)? {
+        crate::Resolved::Url(url) => url,
+        crate::Resolved::Auth(_, core) => return Ok(Entrypoint::Auth(Auth { client, core })),
+        crate::Resolved::Withdraw(_, core) => {
+            return Ok(Entrypoint::Withdraw(Withdraw { client, core }))
+        }
+    };
+
+    flow::entrypoint(&client.get(url.as_str()).await?).map(|query| match query {
+        crate::Entrypoint::Channel(core) => Entrypoint::Channel(Channel { client, core }),
+        crate::Entrypoint::HostedChannel(core) => Entrypoint::HostedChannel(HostedChannel { core }),
+        crate::Entrypoint::Pay(core) => Entrypoint::Pay(Pay { client, core }),
+        crate::Entrypoint::Withdraw(core) => Entrypoint::Withdraw(Withdraw { client, core }),
+    })
+}
+
 #[derive(Clone, Debug)]
 pub enum Entrypoint<'a> {
     Auth(Auth<'a>),
     Channel(Channel<'a>),
+    HostedChannel(HostedChannel),
     Pay(Pay<'a>),
     Withdraw(Withdraw<'a>),
 }
 
+impl Entrypoint<'_> {
+    /// Same entrypoint, holding its own client handle instead of borrowing one, so it can be
+    /// kept around or sent to other tasks.
+    #[must_use]
+    pub fn into_owned(self) -> Entrypoint<'static> {
+        match self {
+            Entrypoint::Auth(a) => Entrypoint::Auth(a.into_owned()),
+            Entrypoint::Channel(c) => Entrypoint::Channel(c.into_owned()),
+            Entrypoint::HostedChannel(h) => Entrypoint::HostedChannel(h),
+            Entrypoint::Pay(p) => Entrypoint::Pay(p.into_owned()),
+            Entrypoint::Withdraw(w) => Entrypoint::Withdraw(w.into_owned()),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Auth<'a> {
-    client: &'a reqwest::Client,
+    client: Handle<'a, dyn Transport>,
     pub core: crate::auth::Entrypoint,
 }
 
 #[derive(Clone, Debug)]
 pub struct Channel<'a> {
-    client: &'a reqwest::Client,
+    client: Handle<'a, dyn Transport>,
     pub core: crate::channel::client::Entrypoint,
 }
 
+#[derive(Clone, Debug)]
+pub struct HostedChannel {
+    pub core: crate::hosted_channel::client::Entrypoint,
+}
+
 #[derive(Clone, Debug)]
 pub struct Pay<'a> {
-    client: &'a reqwest::Client,
+    client: Handle<'a, dyn Transport>,
     pub core: Box<crate::pay::client::Entrypoint>,
 }
 
 #[derive(Clone, Debug)]
 pub struct Withdraw<'a> {
-    client: &'a reqwest::Client,
+    client: Handle<'a, dyn Transport>,
     pub core: crate::withdraw::client::Entrypoint,
 }
 
 impl Auth<'_> {
+    /// Same as [`Entrypoint::into_owned`].
+    #[must_use]
+    pub fn into_owned(self) -> Auth<'static> {
+        Auth {
+            client: self.client.into_owned(),
+            core: self.core,
+        }
+    }
+
     /// # Errors
     ///
     /// Returns errors on network or deserialization failures.
-    pub async fn auth(
-        &self,
-        key: &str,
-        sig: &[u8; 64],
-    ) -> Result<crate::CallbackResponse, &'static str> {
+    pub async fn auth(&self, key: &[u8], sig: &[u8]) -> Result<crate::CallbackResponse, Error> {
         let callback = self.core.auth(key, sig);
 
-        let response = self
-            .client
-            .get(callback.to_string())
-            .send()
-            .await
-            .map_err(|_| "request failed")?;
+        flow::callback(&self.client.get(&callback.to_string()).await?)
+    }
 
-        let bytes = response.bytes().await.map_err(|_| "body failed")?;
-        (&bytes as &[u8]).try_into().map_err(|_| "parse failed")
+    /// # Errors
+    ///
+    /// Returns errors on network or deserialization failures.
+    pub async fn auth_with(
+        &self,
+        key: &crate::auth::linking::LinkingKey,
+    ) -> Result<crate::CallbackResponse, Error> {
+        let sig = key.sign(&self.core.k1);
+        self.auth(&key.public_key(), &sig).await
     }
 }
 
 impl Channel<'_> {
+    /// Same as [`Entrypoint::into_owned`].
+    #[must_use]
+    pub fn into_owned(self) -> Channel<'static> {
+        Channel {
+            client: self.client.into_owned(),
+            core: self.core,
+        }
+    }
+
     /// # Errors
     ///
     /// Returns errors on network or deserialization failures.
@@ -95,78 +201,325 @@ impl Channel<'_> {
         &self,
         remoteid: &str,
         private: bool,
-    ) -> Result<crate::CallbackResponse, &'static str> {
+    ) -> Result<crate::CallbackResponse, Error> {
         let callback = self.core.accept(remoteid, private);
 
-        let response = self
-            .client
-            .get(callback.to_string())
-            .send()
-            .await
-            .map_err(|_| "request failed")?;
-
-        let bytes = response.bytes().await.map_err(|_| "body failed")?;
-        (&bytes as &[u8]).try_into().map_err(|_| "parse failed")
+        flow::callback(&self.client.get(&callback.to_string()).await?)
     }
 
     /// # Errors
     ///
     /// Returns errors on network or deserialization failures.
-    pub async fn cancel(&self, remoteid: &str) -> Result<crate::CallbackResponse, &'static str> {
+    pub async fn cancel(&self, remoteid: &str) -> Result<crate::CallbackResponse, Error> {
         let callback = self.core.cancel(remoteid);
 
-        let response = self
-            .client
-            .get(callback.to_string())
-            .send()
-            .await
-            .map_err(|_| "request failed")?;
-
-        let bytes = response.bytes().await.map_err(|_| "body failed")?;
-        (&bytes as &[u8]).try_into().map_err(|_| "parse failed")
+        flow::callback(&self.client.get(&callback.to_string()).await?)
     }
 }
 
 impl Pay<'_> {
+    /// Same as [`Entrypoint::into_owned`].
+    #[must_use]
+    pub fn into_owned(self) -> Pay<'static> {
+        Pay {
+            client: self.client.into_owned(),
+            core: self.core,
+        }
+    }
+
     /// # Errors
     ///
-    /// Returns errors on network or deserialization failures.
+    /// Returns errors on network or deserialization failures, or on LNURL `ERROR` responses.
     pub async fn invoice(
         &self,
         amount: &crate::pay::Amount,
         comment: Option<&str>,
         convert: Option<&str>,
         payer: Option<crate::pay::PayerInformations>,
-    ) -> Result<crate::pay::client::CallbackResponse, &'static str> {
+    ) -> Result<Invoice, Error> {
         let callback = self.core.invoice(amount, comment, convert, payer);
 
-        let response = self
-            .client
-            .get(callback.to_string())
-            .send()
-            .await
-            .map_err(|_| "request failed")?;
+        flow::invoice(&self.client.get(&callback.to_string()).await?)
+    }
+
+    /// Same as [`Pay::invoice`], but the returned invoice is decoded and checked against
+    /// what was asked for, `network` and the current time.
+    ///
+    /// # Errors
+    ///
+    /// Returns errors on network or deserialization failures, on LNURL `ERROR` responses, or if
+    /// the invoice does not match.
+    pub async fn verified_invoice(
+        &self,
+        amount: &crate::pay::Amount,
+        comment: Option<&str>,
+        convert: Option<&str>,
+        payer: Option<crate::pay::PayerInformations>,
+        network: crate::bolt11::Network,
+    ) -> Result<VerifiedInvoice, Error> {
+        let callback = self.core.invoice(amount, comment, convert, payer);
+        let response = self.client.get(&callback.to_string()).await?;
 
-        let text = response.text().await.map_err(|_| "body failed")?;
-        text.parse().map_err(|_| "parse failed")
+        flow::verified_invoice(&callback, &response, network)
     }
 }
 
-impl Withdraw<'_> {
+/// Successful pay callback response, see [`Pay::invoice`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Invoice {
+    pub pr: String,
+    pub disposable: bool,
+    pub success_action: Option<crate::pay::client::SuccessAction>,
+    /// Top-level fields not covered by any of the above.
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Successful pay callback response whose invoice was decoded and checked, see
+/// [`Pay::verified_invoice`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VerifiedInvoice {
+    pub pr: String,
+    pub disposable: bool,
+    pub success_action: Option<crate::pay::client::SuccessAction>,
+    pub invoice: crate::bolt11::Invoice,
+}
+
+impl<'a> Withdraw<'a> {
+    /// Same as [`Entrypoint::into_owned`].
+    #[must_use]
+    pub fn into_owned(self) -> Withdraw<'static> {
+        Withdraw {
+            client: self.client.into_owned(),
+            core: self.core,
+        }
+    }
+
     /// # Errors
     ///
     /// Returns errors on network or deserialization failures.
-    pub async fn submit(&self, pr: &str) -> Result<crate::CallbackResponse, &'static str> {
-        let callback = self.core.submit(pr);
+    pub async fn submit(
+        &self,
+        pr: &str,
+        balance_notify: Option<&url::Url>,
+    ) -> Result<crate::CallbackResponse, Error> {
+        let callback = self.core.submit(pr, balance_notify);
+
+        flow::callback(&self.client.get(&callback.to_string()).await?)
+    }
+
+    /// # Errors
+    ///
+    /// Returns errors on missing balance check, network or deserialization failures, or on LNURL
+    /// `ERROR` responses.
+    pub async fn balance_check(&self) -> Result<Withdraw<'a>, Error> {
+        let url = flow::balance_check(&self.core)?;
+        let response = self.client.get(url.as_str()).await?;
+
+        Ok(Withdraw {
+            client: self.client.clone(),
+            core: flow::balance_check_response(&response)?,
+        })
+    }
+
+    /// Resolves the LUD-19 `payLink`, for topping up what is being withdrawn from.
+    ///
+    /// # Errors
+    ///
+    /// Returns errors on missing pay link, network or deserialization failures.
+    pub async fn pay_link(&self) -> Result<Pay<'a>, Error> {
+        let link = flow::pay_link(&self.core)?;
+
+        match entrypoint(self.client.clone(), link).await? {
+            Entrypoint::Pay(pay) => Ok(pay),
+            _ => Err(Error::Unexpected("pay")),
+        }
+    }
+}
+
+/// Failures of client operations, by layer.
+#[derive(Debug)]
+pub enum Error {
+    /// The input could not be turned into something to be queried.
+    Resolve(crate::error::ResolveError),
+    /// The request could not be made or its body could not be read.
+    Transport(transport::TransportError),
+    /// The service replied something that could not be understood.
+    Parse(crate::error::ParseError),
+    /// The service replied with an LNURL `ERROR` response, `extra` holding its top-level fields
+    /// besides `status` and `reason`.
+    Protocol {
+        reason: String,
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+    /// The service replied with a non-2xx status and no LNURL reason.
+    Status(u16),
+    /// The returned invoice does not match what was asked for.
+    Invoice(crate::error::InvoiceError),
+    /// The entrypoint lacks what the operation needs.
+    Missing(&'static str),
+    /// The service replied with a different kind of entrypoint than expected.
+    Unexpected(&'static str),
+    /// The system clock is before the unix epoch.
+    Clock(std::time::SystemTimeError),
+}
 
-        let response = self
-            .client
-            .get(callback.to_string())
-            .send()
+impl Error {
+    /// Whether retrying the same operation may succeed, as with timeouts, refused connections
+    /// or server side failures.
+    #[must_use]
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Transport(e) => e.is_transient(),
+            Error::Status(s) => matches!(s, 429 | 500 | 502..=599),
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Resolve(e) => write!(f, "resolve failed: {e}"),
+            Error::Transport(_) => f.write_str("request failed"),
+            Error::Parse(e) => write!(f, "parse failed: {e}"),
+            Error::Protocol { reason, .. } => write!(f, "service error: {reason}"),
+            Error::Status(status) => write!(f, "service replied status {status}"),
+            Error::Invoice(e) => write!(f, "invoice rejected: {e}"),
+            Error::Missing(what) => write!(f, "no {what}"),
+            Error::Unexpected(what) => write!(f, "not {what}"),
+            Error::Clock(_) => f.write_str("clock failed"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Resolve(e) => Some(e),
+            Error::Transport(e) => Some(e),
+            Error::Parse(e) => Some(e),
+            Error::Invoice(e) => Some(e),
+            Error::Clock(e) => Some(e),
+            Error::Protocol { .. }
+            | Error::Status(_)
+            | Error::Missing(_)
+            | Error::Unexpected(_) => None,
+        }
+    }
+}
+
+impl From<crate::error::ResolveError> for Error {
+    fn from(e: crate::error::ResolveError) -> Self {
+        Error::Resolve(e)
+    }
+}
+
+impl From<transport::TransportError> for Error {
+    fn from(e: transport::TransportError) -> Self {
+        Error::Transport(e)
+    }
+}
+
+impl From<crate::error::ParseError> for Error {
+    fn from(e: crate::error::ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+impl From<crate::error::InvoiceError> for Error {
+    fn from(e: crate::error::InvoiceError) -> Self {
+        Error::Invoice(e)
+    }
+}
+
+impl From<std::time::SystemTimeError> for Error {
+    fn from(e: std::time::SystemTimeError) -> Self {
+        Error::Clock(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[derive(Debug)]
+    struct Fixed(std::collections::HashMap<&'static str, (u16, &'static str)>);
+
+    impl super::Transport for Fixed {
+        fn get<'a>(&'a self, url: &'a str) -> super::transport::ResponseFuture<'a> {
+            let response = self
+                .0
+                .get(url)
+                .map(|(status, body)| super::transport::Response {
+                    status: *status,
+                    body: body.as_bytes().to_vec(),
+                });
+
+            Box::pin(async move {
+                response.ok_or_else(|| super::transport::TransportError::new("unknown url"))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_transport() {
+        let client = super::Client::new(Fixed(
+            [
+                (
+                    "https://there.is/w",
+                    (
+                        200,
+                        r#"{
+                            "tag": "withdrawRequest",
+                            "k1": "caum",
+                            "callback": "https://there.is/cb",
+                            "minWithdrawable": 314,
+                            "maxWithdrawable": 315,
+                            "defaultDescription": "descricao"
+                        }"#,
+                    ),
+                ),
+                (
+                    "https://there.is/cb?k1=caum&pr=pierre",
+                    (200, r#"{ "status": "OK" }"#),
+                ),
+                (
+                    "https://there.is/gone",
+                    (410, r#"{ "status": "ERROR", "reason": "used" }"#),
+                ),
+                ("https://there.is/down", (503, "")),
+            ]
+            .into_iter()
+            .collect(),
+        ));
+
+        let Ok(super::Entrypoint::Withdraw(w)) = client.entrypoint("lnurlw://there.is/w").await
+        else {
+            panic!("expected withdraw");
+        };
+
+        let response = w.submit("pierre", None).await.expect("submit");
+        assert!(matches!(response, crate::CallbackResponse::Ok { .. }));
+
+        let w = w.into_owned();
+        let response = tokio::spawn(async move { w.submit("pierre", None).await })
             .await
-            .map_err(|_| "request failed")?;
+            .expect("join")
+            .expect("submit");
+        assert!(matches!(response, crate::CallbackResponse::Ok { .. }));
+
+        let err = client.entrypoint("lnurlw://there.is/gone").await.err();
+        assert!(matches!(
+            err,
+            Some(super::Error::Protocol { reason, .. }) if reason == "used"
+        ));
+
+        let err = client.entrypoint("lnurlw://there.is/down").await.err();
+        assert!(matches!(&err, Some(super::Error::Status(503))));
+        assert!(err.expect("error").is_transient());
 
-        let bytes = response.bytes().await.map_err(|_| "body failed")?;
-        (&bytes as &[u8]).try_into().map_err(|_| "parse failed")
+        let err = client.entrypoint("lnurlw://there.is/nope").await.err();
+        assert!(matches!(&err, Some(super::Error::Transport(_))));
+        assert!(!err.expect("error").is_transient());
     }
 }
@@ -0,0 +1,429 @@
+//! Synchronous counterpart of [`super::Client`], for callers without an async runtime.
+
+use super::{
+    transport::{Response, TransportError},
+    Error,
+};
+
+/// Blocking HTTP stack the flows run on, with a `reqwest` implementation behind the
+/// `client-blocking` feature.
+pub trait Transport: std::fmt::Debug + Send + Sync {
+    /// Performs a `GET` on `url`, returning the status and the whole body.
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case the request cannot be made or the body cannot be read.
+    fn get(&self, url: &str) -> Result<Response, TransportError>;
+}
+
+#[cfg(feature = "client-blocking")]
+impl Transport for reqwest::blocking::Client {
+    fn get(&self, url: &str) -> Result<Response, TransportError> {
+        let response = self.get(url).send()?;
+        let status = response.status().as_u16();
+        let body = response.bytes()?.to_vec();
+
+        Ok(Response { status, body })
+    }
+}
+
+/// Blocking `reqwest` transport that stops reading bodies past a size limit.
+#[cfg(feature = "client-blocking")]
+#[derive(Clone, Debug)]
+pub struct Reqwest {
+    client: reqwest::blocking::Client,
+    max_body_size: Option<usize>,
+}
+
+#[cfg(feature = "client-blocking")]
+impl Reqwest {
+    #[must_use]
+    pub fn new(client: reqwest::blocking::Client, max_body_size: Option<usize>) -> Self {
+        Reqwest {
+            client,
+            max_body_size,
+        }
+    }
+}
+
+#[cfg(feature = "client-blocking")]
+impl Transport for Reqwest {
+    fn get(&self, url: &str) -> Result<Response, TransportError> {
+        use std::io::Read;
+
+        let Some(limit) = self.max_body_size else {
+            return Transport::get(&self.client, url);
+        };
+
+        let mut response = self.client.get(url).send()?;
+        let mut body = super::transport::LimitedBody::new(limit, response.content_length())?;
+
+        let mut chunk = [0; 8192];
+        loop {
+            let read = response.read(&mut chunk).map_err(TransportError::new)?;
+            if read == 0 {
+                break;
+            }
+
+            body.extend(&chunk[..read])?;
+        }
+
+        Ok(body.into_response(response.status().as_u16()))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Client(std::sync::Arc<dyn Transport>);
+
+#[cfg(feature = "client-blocking")]
+impl Default for Client {
+    fn default() -> Self {
+        super::Builder::default()
+            .build_blocking()
+            .expect("default client")
+    }
+}
+
+impl Client {
+    /// Settings for a `reqwest` backed client, see [`super::Builder::build_blocking`].
+    #[cfg(feature = "client-blocking")]
+    #[must_use]
+    pub fn builder() -> super::Builder {
+        super::Builder::default()
+    }
+
+    /// Client running its flows on `transport`.
+    pub fn new(transport: impl Transport + 'static) -> Self {
+        Client(std::sync::Arc::new(transport))
+    }
+
+    /// # Errors
+    ///
+    /// Returns errors on network or deserialization failures, or on LNURL `ERROR` responses.
+    pub fn entrypoint(&self, s: &str) -> Result<Entrypoint<'_>, Error> {
+        entrypoint(super::Handle::Borrowed(&self.0), s)
+    }
+}
+
+fn entrypoint<'a>(
+    client: super::Handle<'a, dyn Transport>,
+    s: &str,
+) -> Result<Entrypoint<'a>, Error> {
+    let url = match crate::resolve(s)? {
+        crate::Resolved::Url(url) => url,
+        crate::Resolved::Auth(_, core) => return Ok(Entrypoint::Auth(Auth { client, core })),
+        crate::Resolved::Withdraw(_, core) => {
+            return Ok(Entrypoint::Withdraw(Withdraw { client, core }))
+        }
+    };
+
+    super::flow::entrypoint(&client.get(url.as_str())?).map(|query| match query {
+        crate::Entrypoint::Channel(core) => Entrypoint::Channel(Channel { client, core }),
+        crate::Entrypoint::HostedChannel(core) => {
+            Entrypoint::HostedChannel(super::HostedChannel { core })
+        }
+        crate::Entrypoint::Pay(core) => Entrypoint::Pay(Pay { client, core }),
+        crate::Entrypoint::Withdraw(core) => Entrypoint::Withdraw(Withdraw { client, core }),
+    })
+}
+
+#[derive(Clone, Debug)]
+pub enum Entrypoint<'a> {
+    Auth(Auth<'a>),
+    Channel(Channel<'a>),
+    HostedChannel(super::HostedChannel),
+    Pay(Pay<'a>),
+    Withdraw(Withdraw<'a>),
+}
+
+impl Entrypoint<'_> {
+    /// Same entrypoint, holding its own client handle instead of borrowing one, so it can be
+    /// kept around or sent to other tasks.
+    #[must_use]
+    pub fn into_owned(self) -> Entrypoint<'static> {
+        match self {
+            Entrypoint::Auth(a) => Entrypoint::Auth(a.into_owned()),
+            Entrypoint::Channel(c) => Entrypoint::Channel(c.into_owned()),
+            Entrypoint::HostedChannel(h) => Entrypoint::HostedChannel(h),
+            Entrypoint::Pay(p) => Entrypoint::Pay(p.into_owned()),
+            Entrypoint::Withdraw(w) => Entrypoint::Withdraw(w.into_owned()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Auth<'a> {
+    client: super::Handle<'a, dyn Transport>,
+    pub core: crate::auth::Entrypoint,
+}
+
+#[derive(Clone, Debug)]
+pub struct Channel<'a> {
+    client: super::Handle<'a, dyn Transport>,
+    pub core: crate::channel::client::Entrypoint,
+}
+
+#[derive(Clone, Debug)]
+pub struct Pay<'a> {
+    client: super::Handle<'a, dyn Transport>,
+    pub core: Box<crate::pay::client::Entrypoint>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Withdraw<'a> {
+    client: super::Handle<'a, dyn Transport>,
+    pub core: crate::withdraw::client::Entrypoint,
+}
+
+impl Auth<'_> {
+    /// Same as [`Entrypoint::into_owned`].
+    #[must_use]
+    pub fn into_owned(self) -> Auth<'static> {
+        Auth {
+            client: self.client.into_owned(),
+            core: self.core,
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Returns errors on network or deserialization failures.
+    pub fn auth(&self, key: &[u8], sig: &[u8]) -> Result<crate::CallbackResponse, Error> {
+        let callback = self.core.auth(key, sig);
+
+        super::flow::callback(&self.client.get(&callback.to_string())?)
+    }
+
+    /// # Errors
+    ///
+    /// Returns errors on network or deserialization failures.
+    pub fn auth_with(
+        &self,
+        key: &crate::auth::linking::LinkingKey,
+    ) -> Result<crate::CallbackResponse, Error> {
+        let sig = key.sign(&self.core.k1);
+        self.auth(&key.public_key(), &sig)
+    }
+}
+
+impl Channel<'_> {
+    /// Same as [`Entrypoint::into_owned`].
+    #[must_use]
+    pub fn into_owned(self) -> Channel<'static> {
+        Channel {
+            client: self.client.into_owned(),
+            core: self.core,
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Returns errors on network or deserialization failures.
+    pub fn accept(&self, remoteid: &str, private: bool) -> Result<crate::CallbackResponse, Error> {
+        let callback = self.core.accept(remoteid, private);
+
+        super::flow::callback(&self.client.get(&callback.to_string())?)
+    }
+
+    /// # Errors
+    ///
+    /// Returns errors on network or deserialization failures.
+    pub fn cancel(&self, remoteid: &str) -> Result<crate::CallbackResponse, Error> {
+        let callback = self.core.cancel(remoteid);
+
+        super::flow::callback(&self.client.get(&callback.to_string())?)
+    }
+}
+
+impl Pay<'_> {
+    /// Same as [`Entrypoint::into_owned`].
+    #[must_use]
+    pub fn into_owned(self) -> Pay<'static> {
+        Pay {
+            client: self.client.into_owned(),
+            core: self.core,
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Returns errors on network or deserialization failures, or on LNURL `ERROR` responses.
+    pub fn invoice(
+        &self,
+        amount: &crate::pay::Amount,
+        comment: Option<&str>,
+        convert: Option<&str>,
+        payer: Option<crate::pay::PayerInformations>,
+    ) -> Result<super::Invoice, Error> {
+        let callback = self.core.invoice(amount, comment, convert, payer);
+
+        super::flow::invoice(&self.client.get(&callback.to_string())?)
+    }
+
+    /// Same as [`Pay::invoice`], but the returned invoice is decoded and checked against
+    /// what was asked for, `network` and the current time.
+    ///
+    /// # Errors
+    ///
+    /// Returns errors on network or deserialization failures, on LNURL `ERROR` responses, or if
+    /// the invoice does not match.
+    pub fn verified_invoice(
+        &self,
+        amount: &crate::pay::Amount,
+        comment: Option<&str>,
+        convert: Option<&str>,
+        payer: Option<crate::pay::PayerInformations>,
+        network: crate::bolt11::Network,
+    ) -> Result<super::VerifiedInvoice, Error> {
+        let callback = self.core.invoice(amount, comment, convert, payer);
+        let response = self.client.get(&callback.to_string())?;
+
+        super::flow::verified_invoice(&callback, &response, network)
+    }
+}
+
+impl<'a> Withdraw<'a> {
+    /// Same as [`Entrypoint::into_owned`].
+    #[must_use]
+    pub fn into_owned(self) -> Withdraw<'static> {
+        Withdraw {
+            client: self.client.into_owned(),
+            core: self.core,
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Returns errors on network or deserialization failures.
+    pub fn submit(
+        &self,
+        pr: &str,
+        balance_notify: Option<&url::Url>,
+    ) -> Result<crate::CallbackResponse, Error> {
+        let callback = self.core.submit(pr, balance_notify);
+
+        super::flow::callback(&self.client.get(&callback.to_string())?)
+    }
+
+    /// # Errors
+    ///
+    /// Returns errors on missing balance check, network or deserialization failures, or on LNURL
+    /// `ERROR` responses.
+    pub fn balance_check(&self) -> Result<Withdraw<'a>, Error> {
+        let url = super::flow::balance_check(&self.core)?;
+        let response = self.client.get(url.as_str())?;
+
+        Ok(Withdraw {
+            client: self.client.clone(),
+            core: super::flow::balance_check_response(&response)?,
+        })
+    }
+
+    /// Resolves the LUD-19 `payLink`, for topping up what is being withdrawn from.
+    ///
+    /// # Errors
+    ///
+    /// Returns errors on missing pay link, network or deserialization failures.
+    pub fn pay_link(&self) -> Result<Pay<'a>, Error> {
+        let link = super::flow::pay_link(&self.core)?;
+
+        match entrypoint(self.client.clone(), link)? {
+            Entrypoint::Pay(pay) => Ok(pay),
+            _ => Err(Error::Unexpected("pay")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[derive(Debug)]
+    struct Fixed(std::collections::HashMap<&'static str, (u16, &'static str)>);
+
+    impl super::Transport for Fixed {
+        fn get(&self, url: &str) -> Result<super::Response, super::TransportError> {
+            let (status, body) = self
+                .0
+                .get(url)
+                .ok_or_else(|| super::TransportError::new("unknown url"))?;
+
+            Ok(super::Response {
+                status: *status,
+                body: body.as_bytes().to_vec(),
+            })
+        }
+    }
+
+    #[test]
+    fn custom_transport() {
+        let client = super::Client::new(Fixed(
+            [
+                (
+                    "https://there.is/p",
+                    (
+                        200,
+                        r#"{
+                            "tag": "payRequest",
+                            "callback": "https://there.is/cb",
+                            "metadata": "[[\"text/plain\", \"descricao\"]]",
+                            "minSendable": 314,
+                            "maxSendable": 315
+                        }"#,
+                    ),
+                ),
+                (
+                    "https://there.is/cb?amount=314",
+                    (200, r#"{ "pr": "pierre" }"#),
+                ),
+                (
+                    "https://there.is/cb?amount=315",
+                    (
+                        200,
+                        r#"{ "status": "ERROR", "reason": "too much", "max": 314 }"#,
+                    ),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        ));
+
+        let Ok(super::Entrypoint::Pay(p)) = client.entrypoint("lnurlp://there.is/p") else {
+            panic!("expected pay");
+        };
+
+        let response = p
+            .invoice(&crate::pay::Amount::Millisatoshis(314), None, None, None)
+            .expect("invoice");
+
+        assert_eq!(response.pr, "pierre");
+
+        let owned = p.clone().into_owned();
+        let response = std::thread::spawn(move || {
+            owned.invoice(&crate::pay::Amount::Millisatoshis(314), None, None, None)
+        })
+        .join()
+        .expect("join")
+        .expect("invoice");
+
+        assert_eq!(response.pr, "pierre");
+
+        let result = p.invoice(&crate::pay::Amount::Millisatoshis(315), None, None, None);
+
+        assert!(matches!(
+            result,
+            Err(super::Error::Protocol { reason, extra }) if reason == "too much" && extra["max"] == 314
+        ));
+
+        let result = p.verified_invoice(
+            &crate::pay::Amount::Millisatoshis(315),
+            None,
+            None,
+            None,
+            crate::bolt11::Network::Bitcoin,
+        );
+
+        assert!(matches!(
+            result,
+            Err(super::Error::Protocol { reason, extra }) if reason == "too much" && extra["max"] == 314
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+use super::transport::TransportError;
+use std::time::Duration;
+
+/// Applies the settings of a [`Builder`] to a `reqwest` async or blocking client builder, whose
+/// methods share names but no trait, and builds the client.
+#[cfg(any(feature = "client", feature = "client-blocking"))]
+macro_rules! configure {
+    ($settings:expr, $builder:expr) => {{
+        let settings = &$settings;
+        let mut builder = $builder
+            .default_headers(settings.header_map()?)
+            .redirect(settings.redirect_policy());
+
+        if let Some(timeout) = settings.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        if let Some(timeout) = settings.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+
+        if let Some(user_agent) = &settings.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+
+        if let Some(proxy) = settings.reqwest_proxy()? {
+            builder = builder.proxy(proxy);
+        }
+
+        builder.build().map_err(TransportError::new)?
+    }};
+}
+
+/// Redirects followed unless configured otherwise, as `reqwest` does.
+const MAX_REDIRECTS: usize = 10;
+
+/// Failure of following more redirects than allowed, see [`Builder::max_redirects`].
+#[derive(Debug)]
+struct TooManyRedirects;
+
+impl std::fmt::Display for TooManyRedirects {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("too many redirects")
+    }
+}
+
+impl std::error::Error for TooManyRedirects {}
+
+/// Settings for the `reqwest` backed clients, applied to every request of every flow.
+#[derive(Clone, Debug)]
+pub struct Builder {
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    max_body_size: usize,
+    max_redirects: Option<usize>,
+    user_agent: Option<String>,
+    headers: Vec<(String, String)>,
+    proxy: Option<Proxy>,
+}
+
+#[derive(Clone, Debug)]
+enum Proxy {
+    All(String),
+    Onion(String),
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Builder {
+            timeout: None,
+            connect_timeout: None,
+            max_body_size: super::transport::DEFAULT_MAX_BODY_SIZE,
+            max_redirects: None,
+            user_agent: None,
+            headers: Vec::new(),
+            proxy: None,
+        }
+    }
+}
+
+impl Builder {
+    /// Limit for each whole request, from connecting to reading the last byte of the body.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    #[must_use]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Bodies are read up to `bytes`, failing as soon as more arrive instead of buffering them.
+    /// Defaults to [`super::transport::DEFAULT_MAX_BODY_SIZE`].
+    #[must_use]
+    pub fn max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = bytes;
+        self
+    }
+
+    /// Redirects followed before failing, with `0` meaning none are. Defaults to `10`.
+    ///
+    /// Redirects to clearnet urls other than `https` ones always fail, as entrypoints do.
+    #[must_use]
+    pub fn max_redirects(mut self, redirects: usize) -> Self {
+        self.max_redirects = Some(redirects);
+        self
+    }
+
+    #[must_use]
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Header sent along every request.
+    #[must_use]
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Proxy every request goes through, as in `socks5h://127.0.0.1:9050`.
+    ///
+    /// SOCKS proxies need the `socks` feature. Prefer `socks5h` over `socks5` so that
+    /// names are resolved by the proxy, which onion services require.
+    #[must_use]
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(Proxy::All(url.into()));
+        self
+    }
+
+    /// Same as [`Builder::proxy`], but only requests to `.onion` hosts go through it.
+    #[must_use]
+    pub fn onion_proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(Proxy::Onion(url.into()));
+        self
+    }
+
+    /// # Errors
+    ///
+    /// Returns error in case a header or the proxy is invalid or the HTTP stack cannot be set up.
+    #[cfg(feature = "client")]
+    pub fn build(self) -> Result<super::Client, super::Error> {
+        let client = configure!(self, reqwest::Client::builder());
+
+        Ok(super::Client::new(super::transport::Reqwest::new(
+            client,
+            Some(self.max_body_size),
+        )))
+    }
+
+    /// Same as [`Builder::build`], for the blocking client.
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case a header or the proxy is invalid or the HTTP stack cannot be set up.
+    #[cfg(feature = "client-blocking")]
+    pub fn build_blocking(self) -> Result<super::blocking::Client, super::Error> {
+        let client = configure!(self, reqwest::blocking::Client::builder());
+
+        Ok(super::blocking::Client::new(super::blocking::Reqwest::new(
+            client,
+            Some(self.max_body_size),
+        )))
+    }
+
+    #[cfg(any(feature = "client", feature = "client-blocking"))]
+    fn header_map(&self) -> Result<reqwest::header::HeaderMap, TransportError> {
+        use reqwest::header::{HeaderName, HeaderValue};
+
+        self.headers
+            .iter()
+            .map(|(name, value)| {
+                let name = HeaderName::from_bytes(name.as_bytes()).map_err(TransportError::new)?;
+                let value = HeaderValue::from_str(value).map_err(TransportError::new)?;
+                Ok((name, value))
+            })
+            .collect()
+    }
+
+    #[cfg(any(feature = "client", feature = "client-blocking"))]
+    fn reqwest_proxy(&self) -> Result<Option<reqwest::Proxy>, TransportError> {
+        match &self.proxy {
+            None => Ok(None),
+            Some(Proxy::All(url)) => reqwest::Proxy::all(url)
+                .map(Some)
+                .map_err(TransportError::new),
+            Some(Proxy::Onion(url)) => {
+                reqwest::Proxy::all(url).map_err(TransportError::new)?;
+                let proxy = url::Url::parse(url).map_err(TransportError::new)?;
+
+                Ok(Some(reqwest::Proxy::custom(move |u| {
+                    crate::core::is_onion(u).then(|| proxy.clone())
+                })))
+            }
+        }
+    }
+
+    #[cfg(any(feature = "client", feature = "client-blocking"))]
+    fn redirect_policy(&self) -> reqwest::redirect::Policy {
+        let max = self.max_redirects.unwrap_or(MAX_REDIRECTS);
+
+        reqwest::redirect::Policy::custom(move |attempt| {
+            if max == 0 {
+                attempt.stop()
+            } else if attempt.previous().len() >= max {
+                attempt.error(TooManyRedirects)
+            } else if !crate::core::is_secure(attempt.url()) {
+                attempt.error(crate::error::ResolveError::Insecure)
+            } else {
+                attempt.follow()
+            }
+        })
+    }
+}
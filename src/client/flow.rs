@@ -0,0 +1,97 @@
+//! Request building and response parsing of the client flows, shared by [`super::Client`] and
+//! [`super::blocking::Client`] so each of them only does the transport calls.
+
+use super::{transport::Response, Error};
+
+/// Generic callback response of `response`, as replied to auth, channel and withdraw callbacks.
+pub(super) fn callback(response: &Response) -> Result<crate::CallbackResponse, Error> {
+    Ok((&response.body as &[u8]).try_into()?)
+}
+
+/// Entrypoint of `response`, turning LNURL `ERROR` bodies and non-2xx statuses into errors.
+pub(super) fn entrypoint(response: &Response) -> Result<crate::Entrypoint, Error> {
+    if let Ok(crate::CallbackResponse::Error { reason, extra }) =
+        (&response.body as &[u8]).try_into()
+    {
+        return Err(Error::Protocol { reason, extra });
+    }
+
+    if !response.is_success() {
+        return Err(Error::Status(response.status));
+    }
+
+    Ok((&response.body as &[u8]).try_into()?)
+}
+
+/// URL of the LUD-14 balance check of `core`.
+pub(super) fn balance_check(
+    core: &crate::withdraw::client::Entrypoint,
+) -> Result<&url::Url, Error> {
+    core.balance_check
+        .as_ref()
+        .ok_or(Error::Missing("balance check"))
+}
+
+/// Refreshed withdraw entrypoint of a LUD-14 balance check `response`.
+pub(super) fn balance_check_response(
+    response: &Response,
+) -> Result<crate::withdraw::client::Entrypoint, Error> {
+    match entrypoint(response)? {
+        crate::Entrypoint::Withdraw(core) => Ok(core),
+        _ => Err(Error::Unexpected("withdraw")),
+    }
+}
+
+/// LUD-19 `payLink` of `core`.
+pub(super) fn pay_link(core: &crate::withdraw::client::Entrypoint) -> Result<&str, Error> {
+    core.pay_link.as_deref().ok_or(Error::Missing("pay link"))
+}
+
+/// Pay callback response of `response`, turning LNURL `ERROR` responses into errors.
+pub(super) fn invoice(response: &Response) -> Result<super::Invoice, Error> {
+    let text = String::from_utf8_lossy(&response.body);
+
+    match text.parse()? {
+        crate::pay::client::CallbackResponse::Error { reason, extra } => {
+            Err(Error::Protocol { reason, extra })
+        }
+        crate::pay::client::CallbackResponse::Ok {
+            pr,
+            disposable,
+            success_action,
+            extra,
+        } => Ok(super::Invoice {
+            pr,
+            disposable,
+            success_action,
+            extra,
+        }),
+    }
+}
+
+/// Same as [`invoice`], but checks the returned invoice against `callback`, `network` and the
+/// current time.
+pub(super) fn verified_invoice(
+    callback: &crate::pay::client::Callback<'_>,
+    response: &Response,
+    network: crate::bolt11::Network,
+) -> Result<super::VerifiedInvoice, Error> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs();
+
+    let super::Invoice {
+        pr,
+        disposable,
+        success_action,
+        ..
+    } = invoice(response)?;
+    let invoice = callback.verify(&pr, network, now)?;
+
+    Ok(super::VerifiedInvoice {
+        pr,
+        disposable,
+        success_action,
+        invoice,
+    })
+}
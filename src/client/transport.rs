@@ -0,0 +1,280 @@
+use std::{future::Future, pin::Pin};
+
+/// Future returned by [`Transport::get`].
+pub type ResponseFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Response, TransportError>> + Send + 'a>>;
+
+/// HTTP stack the client flows run on, with a `reqwest` implementation behind the `client` feature.
+pub trait Transport: std::fmt::Debug + Send + Sync {
+    /// Performs a `GET` on `url`, resolving to the status and the whole body.
+    fn get<'a>(&'a self, url: &'a str) -> ResponseFuture<'a>;
+}
+
+#[derive(Clone, Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Failure of a [`Transport`], keeping whatever the HTTP stack reported as source.
+#[derive(Debug)]
+pub struct TransportError {
+    transient: bool,
+    source: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl TransportError {
+    pub fn new(source: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        TransportError {
+            transient: false,
+            source: source.into(),
+        }
+    }
+
+    /// Same as [`TransportError::new`], but worth retrying, as with timeouts.
+    pub fn transient(source: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        TransportError {
+            transient: true,
+            source: source.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn is_transient(&self) -> bool {
+        self.transient
+    }
+}
+
+impl std::fmt::Display for TransportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("request failed")
+    }
+}
+
+impl std::error::Error for TransportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+#[cfg(any(feature = "client", feature = "client-blocking"))]
+impl From<reqwest::Error> for TransportError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() || e.is_connect() {
+            TransportError::transient(e)
+        } else {
+            TransportError::new(e)
+        }
+    }
+}
+
+/// Size bodies are read up to unless configured otherwise, see `Builder::max_body_size`.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 1 << 20;
+
+/// Failure of reading a body bigger than allowed, see `Builder::max_body_size`.
+#[derive(Clone, Copy, Debug)]
+pub struct BodyTooLarge {
+    pub limit: usize,
+}
+
+impl std::fmt::Display for BodyTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "body larger than {} bytes", self.limit)
+    }
+}
+
+impl std::error::Error for BodyTooLarge {}
+
+/// Body read chunk by chunk, failing as soon as it grows past its limit. Shared by the async and
+/// blocking `reqwest` transports and the in-memory one.
+#[cfg(any(feature = "client", feature = "client-blocking", feature = "server"))]
+pub(super) struct LimitedBody {
+    limit: usize,
+    body: Vec<u8>,
+}
+
+#[cfg(any(feature = "client", feature = "client-blocking", feature = "server"))]
+impl LimitedBody {
+    /// Fails right away if the announced `content_length` is already past `limit`.
+    pub(super) fn new(limit: usize, content_length: Option<u64>) -> Result<Self, TransportError> {
+        if matches!(content_length, Some(length) if length > limit as u64) {
+            return Err(TransportError::new(BodyTooLarge { limit }));
+        }
+
+        Ok(LimitedBody {
+            limit,
+            body: Vec::new(),
+        })
+    }
+
+    pub(super) fn extend(&mut self, chunk: &[u8]) -> Result<(), TransportError> {
+        if self.body.len() + chunk.len() > self.limit {
+            return Err(TransportError::new(BodyTooLarge { limit: self.limit }));
+        }
+
+        self.body.extend_from_slice(chunk);
+        Ok(())
+    }
+
+    pub(super) fn into_response(self, status: u16) -> Response {
+        Response {
+            status,
+            body: self.body,
+        }
+    }
+}
+
+/// `reqwest` transport that stops reading bodies past a size limit.
+#[cfg(feature = "client")]
+#[derive(Clone, Debug)]
+pub struct Reqwest {
+    client: reqwest::Client,
+    max_body_size: Option<usize>,
+}
+
+#[cfg(feature = "client")]
+impl Reqwest {
+    #[must_use]
+    pub fn new(client: reqwest::Client, max_body_size: Option<usize>) -> Self {
+        Reqwest {
+            client,
+            max_body_size,
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl Transport for Reqwest {
+    fn get<'a>(&'a self, url: &'a str) -> ResponseFuture<'a> {
+        Box::pin(async move {
+            let Some(limit) = self.max_body_size else {
+                return Transport::get(&self.client, url).await;
+            };
+
+            let mut response = self.client.get(url).send().await?;
+            let mut body = LimitedBody::new(limit, response.content_length())?;
+
+            while let Some(chunk) = response.chunk().await? {
+                body.extend(&chunk)?;
+            }
+
+            Ok(body.into_response(response.status().as_u16()))
+        })
+    }
+}
+
+#[cfg(feature = "client")]
+impl Transport for reqwest::Client {
+    fn get<'a>(&'a self, url: &'a str) -> ResponseFuture<'a> {
+        Box::pin(async move {
+            let response = self.get(url).send().await?;
+            let status = response.status().as_u16();
+            let body = response.bytes().await?.to_vec();
+
+            Ok(Response { status, body })
+        })
+    }
+}
+
+/// Transport handing requests straight to a [`crate::Server`] router, without any networking.
+///
+/// Only the path and query of urls are looked at, so any host will do.
+#[cfg(feature = "server")]
+#[derive(Clone, Debug)]
+pub struct InMemory {
+    router: axum::Router,
+    max_body_size: usize,
+}
+
+#[cfg(feature = "server")]
+impl InMemory {
+    /// Reads bodies up to [`DEFAULT_MAX_BODY_SIZE`].
+    #[must_use]
+    pub fn new(router: axum::Router) -> Self {
+        InMemory {
+            router,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+
+    /// Same as `Builder::max_body_size`.
+    #[must_use]
+    pub fn max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = bytes;
+        self
+    }
+}
+
+#[cfg(feature = "server")]
+impl Transport for InMemory {
+    fn get<'a>(&'a self, url: &'a str) -> ResponseFuture<'a> {
+        use axum::body::HttpBody;
+        use tower::ServiceExt;
+
+        Box::pin(async move {
+            let url = url::Url::parse(url).map_err(TransportError::new)?;
+            let request = axum::http::Request::get(&url[url::Position::BeforePath..])
+                .body(axum::body::Body::empty())
+                .map_err(TransportError::new)?;
+
+            let response = self
+                .router
+                .clone()
+                .oneshot(request)
+                .await
+                .map_err(TransportError::new)?;
+
+            let status = response.status().as_u16();
+            let mut response = response.into_body();
+            let mut body = LimitedBody::new(self.max_body_size, response.size_hint().upper())?;
+
+            while let Some(frame) =
+                std::future::poll_fn(|cx| Pin::new(&mut response).poll_frame(cx)).await
+            {
+                if let Ok(chunk) = frame.map_err(TransportError::new)?.into_data() {
+                    body.extend(&chunk)?;
+                }
+            }
+
+            Ok(body.into_response(status))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn response_is_success() {
+        let response = |status| super::Response {
+            status,
+            body: Vec::new(),
+        };
+
+        assert!(response(200).is_success());
+        assert!(response(204).is_success());
+        assert!(!response(302).is_success());
+        assert!(!response(404).is_success());
+        assert!(!response(500).is_success());
+    }
+
+    #[cfg(any(feature = "client", feature = "client-blocking", feature = "server"))]
+    #[test]
+    fn limited_body() {
+        assert!(super::LimitedBody::new(4, Some(5)).is_err());
+
+        let mut body = super::LimitedBody::new(4, None).expect("body");
+        body.extend(b"ab").expect("extend");
+        body.extend(b"cd").expect("extend");
+        assert!(body.extend(b"e").is_err());
+
+        let response = body.into_response(200);
+        assert_eq!(response.body, b"abcd");
+    }
+}
@@ -1,26 +1,99 @@
 pub mod auth;
+pub mod bolt11;
 pub mod channel;
+pub mod error;
+pub mod hosted_channel;
+pub mod lnurl;
 pub mod pay;
 pub mod withdraw;
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Resolved {
     Url(url::Url),
     Auth(url::Url, auth::Entrypoint),
     Withdraw(url::Url, withdraw::client::Entrypoint),
 }
 
+/// What an LNURL was found wrapped in, see [`resolve_wrapped`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Wrapper {
+    /// Nothing, as in `lnurl1...`, `lnurlp://...` or `user@domain`.
+    Bare,
+    /// `lightning:` URI, as in `lightning:LNURL1...`.
+    Lightning,
+    /// BIP21 URI with a `lightning` parameter, as in `bitcoin:bc1...?lightning=LNURL1...`.
+    Bitcoin,
+    /// LUD-01 fallback url with a `lightning` parameter, as in `https://site.com/?lightning=LNURL1...`.
+    Fallback,
+}
+
+/// # Errors
+///
+/// Returns error in case `s` cannot be understood.
+pub fn resolve(s: &str) -> Result<Resolved, error::ResolveError> {
+    resolve_wrapped(s).map(|(_, resolved)| resolved)
+}
+
+/// Same as [`resolve`], but also telling what the LNURL was wrapped in.
+///
 /// # Errors
 ///
 /// Returns error in case `s` cannot be understood.
-pub fn resolve(s: &str) -> Result<Resolved, &'static str> {
+pub fn resolve_wrapped(s: &str) -> Result<(Wrapper, Resolved), error::ResolveError> {
+    let (wrapper, s) = unwrap(s)?;
+    resolve_bare(&s).map(|resolved| (wrapper, resolved))
+}
+
+fn unwrap(s: &str) -> Result<(Wrapper, std::borrow::Cow<'_, str>), error::ResolveError> {
+    if let Some(rest) = strip_prefix_ignore_case(s, "lightning:") {
+        return Ok((Wrapper::Lightning, rest.trim_start_matches("//").into()));
+    }
+
+    if let Some(rest) = strip_prefix_ignore_case(s, "bitcoin:") {
+        let (_, query) = rest.split_once('?').ok_or(error::ResolveError::Unknown)?;
+        let lightning = lightning_param(url::form_urlencoded::parse(query.as_bytes()))?;
+        return Ok((Wrapper::Bitcoin, lightning.into()));
+    }
+
+    if strip_prefix_ignore_case(s, "https://").is_some()
+        || strip_prefix_ignore_case(s, "http://").is_some()
+    {
+        let url = url::Url::parse(s)?;
+        let lightning = lightning_param(url.query_pairs())?;
+        return Ok((Wrapper::Fallback, lightning.into()));
+    }
+
+    Ok((Wrapper::Bare, s.into()))
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    s.get(..prefix.len())
+        .filter(|head| head.eq_ignore_ascii_case(prefix))
+        .map(|_| &s[prefix.len()..])
+}
+
+fn lightning_param<'a>(
+    mut pairs: impl Iterator<Item = (std::borrow::Cow<'a, str>, std::borrow::Cow<'a, str>)>,
+) -> Result<String, error::ResolveError> {
+    pairs
+        .find_map(|(k, v)| k.eq_ignore_ascii_case("lightning").then(|| v.into_owned()))
+        .ok_or(error::ResolveError::Unknown)
+}
+
+fn resolve_bare(s: &str) -> Result<Resolved, error::ResolveError> {
     let url = if s.starts_with("lnurl1") || s.starts_with("LNURL1") {
         resolve_bech32(s)
-    } else if s.starts_with("lnurl") || s.starts_with("keyauth") {
-        resolve_scheme(s)
+    } else if let Some(rest) = ["keyauth://", "lnurlc://", "lnurlw://", "lnurlp://"]
+        .iter()
+        .find_map(|scheme| strip_prefix_ignore_case(s, scheme))
+    {
+        resolve_scheme(rest)
     } else if s.contains('@') {
         resolve_address(s)
     } else {
-        Err("unknown")
+        Err(error::ResolveError::Unknown)
     }?;
 
     let tag = url
@@ -40,66 +113,129 @@ pub fn resolve(s: &str) -> Result<Resolved, &'static str> {
     })
 }
 
-fn resolve_bech32(s: &str) -> Result<url::Url, &'static str> {
-    let Ok((hrp, data, _)) = bech32::decode(s) else {
-        return Err("bech32 decode failed");
-    };
+fn resolve_bech32(s: &str) -> Result<url::Url, error::ResolveError> {
+    let (hrp, data, _) = bech32::decode(s)?;
 
     if hrp != "lnurl" {
-        return Err("bech32 hrp invalid");
+        return Err(error::ResolveError::Hrp(hrp));
     }
 
-    let Ok(bytes) = <Vec<u8> as bech32::FromBase32>::from_base32(&data) else {
-        return Err("bech32 data is not bytes");
-    };
+    let bytes = <Vec<u8> as bech32::FromBase32>::from_base32(&data)?;
+    let text = String::from_utf8(bytes).map_err(error::ResolveError::NotText)?;
+    let url = url::Url::parse(&text)?;
 
-    let Ok(text) = String::from_utf8(bytes) else {
-        return Err("bech32 bytes is not string");
-    };
-
-    let Ok(url) = url::Url::parse(&text) else {
-        return Err("bech32 text is not a url");
-    };
+    if !is_secure(&url) {
+        return Err(error::ResolveError::Insecure);
+    }
 
     Ok(url)
 }
 
-fn resolve_scheme(s: &str) -> Result<url::Url, &'static str> {
-    let s = s
-        .trim_start_matches("keyauth://")
-        .trim_start_matches("lnurlc://")
-        .trim_start_matches("lnurlw://")
-        .trim_start_matches("lnurlp://");
+/// `s` is what follows a LUD-17 scheme, as in `site.com/...` for `lnurlp://site.com/...`.
+fn resolve_scheme(s: &str) -> Result<url::Url, error::ResolveError> {
+    onion_aware(url::Url::parse(&format!("https://{s}"))?)
+}
 
-    let Ok(url) = url::Url::parse(&format!("https://{s}")) else {
-        return Err("bad url");
+fn resolve_address(s: &str) -> Result<url::Url, error::ResolveError> {
+    let Some((identifier, domain)) = s.split_once('@') else {
+        return Err(error::ResolveError::Address);
     };
 
+    onion_aware(url::Url::parse(&format!(
+        "https://{domain}/.well-known/lnurlp/{identifier}"
+    ))?)
+}
+
+/// LUD-17 and LUD-16: onion services are reached through plain `http`.
+fn onion_aware(mut url: url::Url) -> Result<url::Url, error::ResolveError> {
+    if is_onion(&url) && url.set_scheme("http").is_err() {
+        return Err(error::ResolveError::Insecure);
+    }
+
     Ok(url)
 }
 
-fn resolve_address(s: &str) -> Result<url::Url, &'static str> {
-    let Some((identifier, domain)) = s.split_once('@') else {
-        return Err("split failed");
-    };
+/// `url` with `query` appended to its query, keeping any fragment where it is.
+pub(crate) fn with_query(url: &url::Url, query: &str) -> url::Url {
+    let mut url = url.clone();
 
-    let Ok(url) = url::Url::parse(&format!("https://{domain}/.well-known/lnurlp/{identifier}"))
-    else {
-        return Err("bad url");
+    let query = match url.query() {
+        Some(existing) if !existing.is_empty() => format!("{existing}&{query}"),
+        _ => String::from(query),
     };
 
-    Ok(url)
+    url.set_query(Some(&query));
+    url
+}
+
+/// LUD-01: clearnet services must use https, onion ones may use http.
+pub(crate) fn is_secure(url: &url::Url) -> bool {
+    match url.scheme() {
+        "https" => true,
+        "http" => is_onion(url),
+        _ => false,
+    }
+}
+
+pub(crate) fn is_onion(url: &url::Url) -> bool {
+    match url.host() {
+        Some(url::Host::Domain(d)) => {
+            matches!(d.rsplit_once('.'), Some((_, tld)) if tld.eq_ignore_ascii_case("onion"))
+        }
+        _ => false,
+    }
+}
+
+/// Same as `hex::serde`, for optional fields.
+#[cfg(feature = "serde")]
+pub(crate) mod hex_option {
+    use serde::{Deserialize, Serialize};
+
+    // serde hands fields over by reference.
+    #[allow(clippy::ref_option)]
+    pub fn serialize<S, T>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+        T: AsRef<[u8]>,
+    {
+        value.as_ref().map(hex::encode).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        T: hex::FromHex,
+        T::Error: std::fmt::Display,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| T::from_hex(s).map_err(serde::de::Error::custom))
+            .transpose()
+    }
 }
 
-#[derive(Debug)]
+/// Top-level fields no flow knows of, leaving out the `owned` ones of the type at hand.
+pub(crate) fn extra(
+    mut fields: serde_json::Map<String, serde_json::Value>,
+    owned: &[&str],
+) -> serde_json::Map<String, serde_json::Value> {
+    for key in owned {
+        fields.remove(*key);
+    }
+
+    fields
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Entrypoint {
     Channel(channel::client::Entrypoint),
+    HostedChannel(hosted_channel::client::Entrypoint),
     Pay(Box<pay::client::Entrypoint>),
     Withdraw(withdraw::client::Entrypoint),
 }
 
 impl TryFrom<&[u8]> for Entrypoint {
-    type Error = &'static str;
+    type Error = error::ParseError;
 
     fn try_from(s: &[u8]) -> Result<Self, Self::Error> {
         #[derive(serde::Deserialize)]
@@ -107,58 +243,82 @@ impl TryFrom<&[u8]> for Entrypoint {
             tag: String,
         }
 
-        let tag = serde_json::from_slice::<Tag>(s).map_err(|_| "deserialize tag failed")?;
+        let tag = serde_json::from_slice::<Tag>(s)?;
 
         if tag.tag == channel::TAG {
-            let cr = s.try_into().map_err(|_| "deserialize data failed")?;
-            Ok(Entrypoint::Channel(cr))
+            Ok(Entrypoint::Channel(s.try_into()?))
+        } else if tag.tag == hosted_channel::TAG {
+            Ok(Entrypoint::HostedChannel(s.try_into()?))
         } else if tag.tag == pay::TAG {
-            let pr = s.try_into().map_err(|_| "deserialize data failed")?;
-            Ok(Entrypoint::Pay(Box::new(pr)))
+            Ok(Entrypoint::Pay(Box::new(s.try_into()?)))
         } else if tag.tag == withdraw::TAG {
-            let wr = s.try_into().map_err(|_| "deserialize data failed")?;
-            Ok(Entrypoint::Withdraw(wr))
+            Ok(Entrypoint::Withdraw(s.try_into()?))
         } else {
-            Err("unknown tag")
+            Err(error::ParseError::UnknownTag(tag.tag))
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CallbackResponse {
-    Error { reason: String },
-    Ok,
+    Error {
+        reason: String,
+        /// Top-level fields besides `status` and `reason`.
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+    Ok {
+        /// Top-level fields besides `status`.
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(tag = "status", rename_all = "UPPERCASE")]
 enum CallbackResponseSerde {
-    Error { reason: String },
-    Ok,
+    Error {
+        reason: String,
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+    Ok {
+        #[serde(flatten)]
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
 }
 
 impl TryFrom<&[u8]> for CallbackResponse {
-    type Error = &'static str;
+    type Error = error::ParseError;
 
-    fn try_from(s: &[u8]) -> Result<Self, &'static str> {
+    fn try_from(s: &[u8]) -> Result<Self, error::ParseError> {
         serde_json::from_slice::<CallbackResponseSerde>(s)
-            .map_err(|_| "deserialize failed")
+            .map_err(error::ParseError::from)
             .map(|a| match a {
-                CallbackResponseSerde::Error { reason } => CallbackResponse::Error { reason },
-                CallbackResponseSerde::Ok => CallbackResponse::Ok,
+                CallbackResponseSerde::Error { reason, extra } => CallbackResponse::Error {
+                    reason,
+                    extra: self::extra(extra, &["status", "reason"]),
+                },
+                CallbackResponseSerde::Ok { extra } => CallbackResponse::Ok {
+                    extra: self::extra(extra, &["status"]),
+                },
             })
     }
 }
 
 impl TryFrom<CallbackResponse> for Vec<u8> {
-    type Error = &'static str;
+    type Error = error::RenderError;
 
     fn try_from(c: CallbackResponse) -> Result<Self, Self::Error> {
         serde_json::to_vec(&match c {
-            CallbackResponse::Error { reason } => CallbackResponseSerde::Error { reason },
-            CallbackResponse::Ok => CallbackResponseSerde::Ok,
+            CallbackResponse::Error { reason, extra } => CallbackResponseSerde::Error {
+                reason,
+                extra: self::extra(extra, &["status", "reason"]),
+            },
+            CallbackResponse::Ok { extra } => CallbackResponseSerde::Ok {
+                extra: self::extra(extra, &["status"]),
+            },
         })
-        .map_err(|_| "deserialize failed")
+        .map_err(error::RenderError::from)
     }
 }
 
@@ -221,6 +381,125 @@ mod tests {
         assert_eq!(url.as_str(), "https://there.is/no?s=poon");
     }
 
+    #[test]
+    fn resolve_onion() {
+        let input = "lnurlp://n0sp00n.onion/no?s=poon";
+        let super::Resolved::Url(url) = super::resolve(input).unwrap() else {
+            panic!("expected resolved url");
+        };
+
+        assert_eq!(url.as_str(), "http://n0sp00n.onion/no?s=poon");
+
+        let super::Resolved::Url(url) = super::resolve("no-spoon@n0sp00n.onion").unwrap() else {
+            panic!("expected resolved url");
+        };
+
+        assert_eq!(
+            url.as_str(),
+            "http://n0sp00n.onion/.well-known/lnurlp/no-spoon"
+        );
+    }
+
+    #[test]
+    fn resolve_bech32_insecure() {
+        let lnurl = |s| crate::lnurl::encode(&url::Url::parse(s).unwrap()).unwrap();
+
+        assert!(matches!(
+            super::resolve(&lnurl("http://there.is/no?s=poon")),
+            Err(super::error::ResolveError::Insecure)
+        ));
+
+        assert!(super::resolve(&lnurl("http://n0sp00n.onion/no?s=poon")).is_ok());
+
+        for input in [
+            "http://localhost:8080/no",
+            "http://127.0.0.1:8080/no",
+            "http://[::1]/no",
+        ] {
+            assert!(matches!(
+                super::resolve(&lnurl(input)),
+                Err(super::error::ResolveError::Insecure)
+            ));
+        }
+    }
+
+    #[test]
+    fn resolve_wrapped() {
+        let lnurl = "LNURL1DP68GURN8GHJ7ARGV4EX2TNFWVHKUMELWV7HQMM0DC6P3ZTW";
+
+        let inputs = [
+            (lnurl.to_owned(), super::Wrapper::Bare),
+            (format!("lightning:{lnurl}"), super::Wrapper::Lightning),
+            (format!("LIGHTNING:{lnurl}"), super::Wrapper::Lightning),
+            (format!("lightning://{lnurl}"), super::Wrapper::Lightning),
+            (
+                format!("bitcoin:bc1qn0sp00n?amount=0.1&lightning={lnurl}"),
+                super::Wrapper::Bitcoin,
+            ),
+            (
+                format!("BITCOIN:BC1QN0SP00N?LIGHTNING={lnurl}"),
+                super::Wrapper::Bitcoin,
+            ),
+            (
+                format!("https://site.com/?lightning={lnurl}"),
+                super::Wrapper::Fallback,
+            ),
+            (
+                format!("HTTPS://site.com/pay?s=poon&LIGHTNING={lnurl}"),
+                super::Wrapper::Fallback,
+            ),
+            (
+                String::from("LNURLP://THERE.IS/no?s=poon"),
+                super::Wrapper::Bare,
+            ),
+            (
+                String::from("LIGHTNING:LNURLP://THERE.IS/no?s=poon"),
+                super::Wrapper::Lightning,
+            ),
+            (
+                String::from("lightning:LnUrlC://there.is/no?s=poon"),
+                super::Wrapper::Lightning,
+            ),
+            (
+                String::from("LIGHTNING:LNURLW://THERE.IS/no?s=poon"),
+                super::Wrapper::Lightning,
+            ),
+            (
+                String::from("LIGHTNING:KEYAUTH://THERE.IS/no?s=poon"),
+                super::Wrapper::Lightning,
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let (wrapper, super::Resolved::Url(url)) = super::resolve_wrapped(&input).unwrap()
+            else {
+                panic!("expected resolved url");
+            };
+
+            assert_eq!(wrapper, expected);
+            assert_eq!(url.as_str(), "https://there.is/no?s=poon");
+        }
+
+        let (wrapper, super::Resolved::Url(url)) =
+            super::resolve_wrapped("lightning:no-spoon@there.is").unwrap()
+        else {
+            panic!("expected resolved url");
+        };
+
+        assert_eq!(wrapper, super::Wrapper::Lightning);
+        assert_eq!(url.as_str(), "https://there.is/.well-known/lnurlp/no-spoon");
+
+        assert!(matches!(
+            super::resolve("bitcoin:bc1qn0sp00n?amount=0.1"),
+            Err(super::error::ResolveError::Unknown)
+        ));
+
+        assert!(matches!(
+            super::resolve("https://site.com/?s=poon"),
+            Err(super::error::ResolveError::Unknown)
+        ));
+    }
+
     #[test]
     fn resolve_auth() {
         let input = "keyauth://site.com\
@@ -253,28 +532,85 @@ mod tests {
     fn callback_response_parse() {
         assert!(matches!(
             (br#"{ "status": "OK" }"# as &[u8]).try_into().unwrap(),
-            super::CallbackResponse::Ok
+            super::CallbackResponse::Ok { extra } if extra.is_empty()
         ));
 
         assert!(matches!(
             (br#"{ "status": "ERROR", "reason": "razao" }"# as &[u8]).try_into().unwrap(),
-            super::CallbackResponse::Error { reason } if reason == "razao"
+            super::CallbackResponse::Error { reason, extra } if reason == "razao" && extra.is_empty()
         ));
     }
 
+    #[test]
+    fn callback_response_parse_extra() {
+        let super::CallbackResponse::Ok { extra } =
+            (br#"{ "status": "OK", "txid": "caum", "fee": 1 }"# as &[u8])
+                .try_into()
+                .unwrap()
+        else {
+            panic!("expected ok");
+        };
+
+        assert_eq!(extra.len(), 2);
+        assert_eq!(extra["txid"], "caum");
+        assert_eq!(extra["fee"], 1);
+
+        let super::CallbackResponse::Error { reason, extra } =
+            (br#"{ "status": "ERROR", "reason": "razao", "retry": true }"# as &[u8])
+                .try_into()
+                .unwrap()
+        else {
+            panic!("expected error");
+        };
+
+        assert_eq!(reason, "razao");
+        assert_eq!(extra.len(), 1);
+        assert_eq!(extra["retry"], true);
+    }
+
     #[test]
     fn callback_response_render() {
         assert_eq!(
-            <Vec::<u8>>::try_from(super::CallbackResponse::Ok).unwrap(),
+            <Vec::<u8>>::try_from(super::CallbackResponse::Ok {
+                extra: serde_json::Map::new()
+            })
+            .unwrap(),
             br#"{"status":"OK"}"#
         );
 
         assert_eq!(
             <Vec::<u8>>::try_from(super::CallbackResponse::Error {
-                reason: String::from("razao")
+                reason: String::from("razao"),
+                extra: serde_json::Map::new()
             })
             .unwrap(),
             br#"{"status":"ERROR","reason":"razao"}"#
         );
+
+        let mut extra = serde_json::Map::new();
+        extra.insert(String::from("txid"), serde_json::Value::from("caum"));
+        extra.insert(String::from("status"), serde_json::Value::from("ERROR"));
+
+        assert_eq!(
+            <Vec::<u8>>::try_from(super::CallbackResponse::Ok { extra }).unwrap(),
+            br#"{"status":"OK","txid":"caum"}"#
+        );
+
+        let mut extra = serde_json::Map::new();
+        extra.insert(String::from("reason"), serde_json::Value::from("outra"));
+        extra.insert(String::from("status"), serde_json::Value::from("OK"));
+        extra.insert(
+            String::from("tag"),
+            serde_json::Value::from("withdrawRequest"),
+        );
+
+        assert_eq!(
+            <Vec::<u8>>::try_from(super::CallbackResponse::Error {
+                reason: String::from("razao"),
+                extra
+            })
+            .unwrap(),
+            br#"{"status":"ERROR","reason":"razao","tag":"withdrawRequest"}"#
+        );
     }
 }
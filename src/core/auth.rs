@@ -1,15 +1,19 @@
 pub const TAG: &str = "login";
 pub mod client;
+pub mod linking;
 pub mod server;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Entrypoint {
     pub url: url::Url,
+    #[cfg_attr(feature = "serde", serde(with = "hex::serde"))]
     pub k1: [u8; 32],
     pub action: Option<Action>,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Action {
     Register,
     Login,
@@ -18,13 +22,15 @@ pub enum Action {
 }
 
 impl TryFrom<&str> for Entrypoint {
-    type Error = &'static str;
+    type Error = crate::error::ParseError;
 
     fn try_from(s: &str) -> Result<Self, Self::Error> {
-        let url = url::Url::parse(s).map_err(|_| "url parse failed")?;
-        let query = url.query().ok_or("missing query")?;
+        let url = url::Url::parse(s)?;
+        let query = url
+            .query()
+            .ok_or(crate::error::ParseError::Missing("query"))?;
         serde_urlencoded::from_str::<de::Entrypoint>(query)
-            .map_err(|_| "deserialize failed")
+            .map_err(crate::error::ParseError::from)
             .map(|c| Entrypoint {
                 url,
                 k1: c.k1,
@@ -40,7 +46,7 @@ impl TryFrom<&str> for Entrypoint {
 
 impl Entrypoint {
     #[must_use]
-    pub fn auth<'a>(&'a self, key: &'a str, sig: &'a [u8; 64]) -> client::Callback<'a> {
+    pub fn auth<'a>(&'a self, key: &'a [u8], sig: &'a [u8]) -> client::Callback<'a> {
         client::Callback {
             url: &self.url,
             k1: &self.k1,
@@ -50,18 +56,6 @@ impl Entrypoint {
     }
 }
 
-mod ser {
-    use serde::Serialize;
-
-    #[derive(Serialize)]
-    pub(super) struct Callback<'a> {
-        #[serde(with = "hex::serde")]
-        pub sig: &'a [u8; 64],
-        #[serde(with = "hex::serde")]
-        pub key: &'a str,
-    }
-}
-
 mod de {
     use serde::Deserialize;
 
@@ -1,8 +1,8 @@
 pub struct Callback<'a> {
     pub url: &'a url::Url,
     pub k1: &'a [u8; 32],
-    pub sig: &'a [u8; 64],
-    pub key: &'a str,
+    pub sig: &'a [u8],
+    pub key: &'a [u8],
 }
 
 impl std::fmt::Display for Callback<'_> {
@@ -24,9 +24,9 @@ mod ser {
     #[derive(Serialize)]
     pub(super) struct Callback<'a> {
         #[serde(with = "hex::serde")]
-        pub sig: &'a [u8; 64],
+        pub sig: &'a [u8],
         #[serde(with = "hex::serde")]
-        pub key: &'a str,
+        pub key: &'a [u8],
     }
 }
 
@@ -41,7 +41,7 @@ mod tests {
         assert_eq!(
             parsed
                 .auth(
-                    "chaves",
+                    b"chaves",
                     b"0123456789012345678901234567890123456789012345678901234567890123"
                 )
                 .to_string(),
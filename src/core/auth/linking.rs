@@ -0,0 +1,269 @@
+use secp256k1::hashes::{hmac, sha256, sha512, Hash, HashEngine};
+
+/// Per-domain key used for signing `k1` challenges (LUD-04 and LUD-18).
+#[derive(Clone, Debug)]
+pub struct LinkingKey(secp256k1::SecretKey);
+
+impl LinkingKey {
+    /// Compressed public key, which is sent as `key` on callbacks.
+    #[must_use]
+    pub fn public_key(&self) -> [u8; 33] {
+        self.0.public_key(secp256k1::SECP256K1).serialize()
+    }
+
+    /// DER encoded signature of `k1`, which is sent as `sig` on callbacks.
+    #[must_use]
+    pub fn sign(&self, k1: &[u8; 32]) -> Vec<u8> {
+        let message = secp256k1::Message::from_digest(*k1);
+        self.0.sign_ecdsa(message).serialize_der().to_vec()
+    }
+}
+
+/// BIP32 master key from which linking keys are derived (LUD-05).
+#[derive(Clone, Debug)]
+pub struct Bip32Master {
+    key: secp256k1::SecretKey,
+    chain_code: [u8; 32],
+}
+
+impl Bip32Master {
+    /// # Errors
+    ///
+    /// Returns error in case `seed` generates an invalid key.
+    pub fn from_seed(seed: &[u8]) -> Result<Self, crate::error::CryptoError> {
+        let (key, chain_code) = hmac_sha512(b"Bitcoin seed", &[seed]);
+        let key = secp256k1::SecretKey::from_slice(&key)?;
+        Ok(Bip32Master { key, chain_code })
+    }
+
+    /// # Errors
+    ///
+    /// Returns error in case `key` is not a valid secret key.
+    pub fn new(key: [u8; 32], chain_code: [u8; 32]) -> Result<Self, crate::error::CryptoError> {
+        let key = secp256k1::SecretKey::from_slice(&key)?;
+        Ok(Bip32Master { key, chain_code })
+    }
+
+    /// Private key at `m/138'/0`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error in the astronomically unlikely case of an invalid derivation.
+    pub fn hashing_key(&self) -> Result<[u8; 32], crate::error::CryptoError> {
+        let key = self.derive(&[HARDENED + 138, 0])?;
+        Ok(key.secret_bytes())
+    }
+
+    /// Linking key at `m/138'/<long1>/<long2>/<long3>/<long4>` for the host of `url`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case `url` has no host or on an invalid derivation.
+    pub fn linking_key(&self, url: &url::Url) -> Result<LinkingKey, crate::error::CryptoError> {
+        let domain = url.host_str().ok_or(crate::error::CryptoError::NoHost)?;
+        let path = derivation_path(&self.hashing_key()?, domain);
+
+        let key = self.derive(&[HARDENED + 138, path[0], path[1], path[2], path[3]])?;
+        Ok(LinkingKey(key))
+    }
+
+    fn derive(&self, path: &[u32]) -> Result<secp256k1::SecretKey, crate::error::CryptoError> {
+        let mut key = self.key;
+        let mut chain_code = self.chain_code;
+
+        for index in path {
+            let (tweak, next_chain_code) = if *index >= HARDENED {
+                hmac_sha512(
+                    &chain_code,
+                    &[&[0], &key.secret_bytes(), &index.to_be_bytes()],
+                )
+            } else {
+                let public = key.public_key(secp256k1::SECP256K1).serialize();
+                hmac_sha512(&chain_code, &[&public, &index.to_be_bytes()])
+            };
+
+            let tweak = secp256k1::Scalar::from_be_bytes(tweak)
+                .map_err(|_| secp256k1::Error::InvalidTweak)?;
+            key = key.add_tweak(&tweak)?;
+            chain_code = next_chain_code;
+        }
+
+        Ok(key)
+    }
+}
+
+/// Phrase to be signed by the node's `signmessage` (LUD-13).
+pub const SIGN_MESSAGE_PHRASE: &str = "DO NOT EVER SIGN THIS TEXT WITH YOUR PRIVATE KEYS! IT IS ONLY USED FOR DERIVATION OF LNURL-AUTH HASHING-KEY, DISCLOSING ITS SIGNATURE WILL COMPROMISE YOUR LNURL-AUTH IDENTITY AND MAY LEAD TO LOSS OF FUNDS!";
+
+/// Signature of [`SIGN_MESSAGE_PHRASE`] from which linking keys are derived (LUD-13).
+#[derive(Clone, Debug)]
+pub struct SignMessage {
+    hashing_key: [u8; 32],
+}
+
+impl SignMessage {
+    /// `signature` being the bytes returned by the node when signing [`SIGN_MESSAGE_PHRASE`].
+    #[must_use]
+    pub fn new(signature: &[u8]) -> Self {
+        let hashing_key = sha256::Hash::hash(signature).to_byte_array();
+        SignMessage { hashing_key }
+    }
+
+    /// Hash of the signature.
+    #[must_use]
+    pub fn hashing_key(&self) -> [u8; 32] {
+        self.hashing_key
+    }
+
+    /// Linking key `hmac_sha256(hashing_key, domain)` for the host of `url`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case `url` has no host or the key is invalid.
+    pub fn linking_key(&self, url: &url::Url) -> Result<LinkingKey, crate::error::CryptoError> {
+        let domain = url.host_str().ok_or(crate::error::CryptoError::NoHost)?;
+        let key = hmac_sha256(&self.hashing_key, domain.as_bytes());
+        let key = secp256k1::SecretKey::from_slice(&key)?;
+        Ok(LinkingKey(key))
+    }
+}
+
+const HARDENED: u32 = 1 << 31;
+
+/// The four path indexes derived from the first 16 bytes of `hmac_sha256(hashing_key, domain)`.
+///
+/// Indexes are used as they come, so values above `2^31` are hardened.
+#[must_use]
+pub fn derivation_path(hashing_key: &[u8; 32], domain: &str) -> [u32; 4] {
+    let material = hmac_sha256(hashing_key, domain.as_bytes());
+
+    let long = |i: usize| {
+        u32::from_be_bytes([
+            material[i],
+            material[i + 1],
+            material[i + 2],
+            material[i + 3],
+        ])
+    };
+    [long(0), long(4), long(8), long(12)]
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut engine = hmac::HmacEngine::<sha256::Hash>::new(key);
+    engine.input(data);
+    hmac::Hmac::<sha256::Hash>::from_engine(engine).to_byte_array()
+}
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> ([u8; 32], [u8; 32]) {
+    let mut engine = hmac::HmacEngine::<sha512::Hash>::new(key);
+    for d in data {
+        engine.input(d);
+    }
+
+    let hash = hmac::Hmac::<sha512::Hash>::from_engine(engine).to_byte_array();
+    let (mut left, mut right) = ([0; 32], [0; 32]);
+    left.copy_from_slice(&hash[..32]);
+    right.copy_from_slice(&hash[32..]);
+    (left, right)
+}
+
+#[cfg(test)]
+mod tests {
+    use secp256k1::hashes::Hash;
+
+    #[test]
+    fn bip32_derivation() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").expect("hex");
+        let master = super::Bip32Master::from_seed(&seed).expect("master");
+
+        assert_eq!(
+            hex::encode(master.key.secret_bytes()),
+            "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35"
+        );
+
+        let key = master.derive(&[super::HARDENED]).expect("derive");
+        assert_eq!(
+            hex::encode(key.secret_bytes()),
+            "edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea"
+        );
+
+        let key = master.derive(&[super::HARDENED, 1]).expect("derive");
+        assert_eq!(
+            hex::encode(key.secret_bytes()),
+            "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368"
+        );
+    }
+
+    #[test]
+    fn linking_key_per_domain() {
+        let master =
+            super::Bip32Master::from_seed(b"oiprazereusouobaites!vamocamigos").expect("master");
+
+        let site = url::Url::parse("https://site.com/auth?k1=caum").expect("url");
+        let same = url::Url::parse("https://site.com/other").expect("url");
+        let other = url::Url::parse("https://other.com/auth?k1=caum").expect("url");
+
+        let key = master.linking_key(&site).expect("key");
+        assert_eq!(
+            key.public_key(),
+            master.linking_key(&same).expect("key").public_key()
+        );
+        assert_ne!(
+            key.public_key(),
+            master.linking_key(&other).expect("key").public_key()
+        );
+
+        let hashing_key = master.hashing_key().expect("hashing key");
+        let path = super::derivation_path(&hashing_key, "site.com");
+        let derived = master
+            .derive(&[super::HARDENED + 138, path[0], path[1], path[2], path[3]])
+            .expect("derive");
+
+        assert_eq!(key.0, derived);
+    }
+
+    #[test]
+    fn sign_message_linking_key() {
+        let source = super::SignMessage::new(b"assinatura do noh");
+
+        assert_eq!(
+            source.hashing_key(),
+            super::sha256::Hash::hash(b"assinatura do noh").to_byte_array()
+        );
+
+        let site = url::Url::parse("https://site.com/auth?k1=caum").expect("url");
+        let same = url::Url::parse("https://site.com/other").expect("url");
+        let other = url::Url::parse("https://other.com/auth?k1=caum").expect("url");
+
+        let key = source.linking_key(&site).expect("key");
+        assert_eq!(
+            key.public_key(),
+            source.linking_key(&same).expect("key").public_key()
+        );
+        assert_ne!(
+            key.public_key(),
+            source.linking_key(&other).expect("key").public_key()
+        );
+
+        assert_eq!(
+            key.0.secret_bytes(),
+            super::hmac_sha256(&source.hashing_key(), b"site.com")
+        );
+    }
+
+    #[test]
+    fn linking_key_sign() {
+        let master =
+            super::Bip32Master::from_seed(b"oiprazereusouobaites!vamocamigos").expect("master");
+        let site = url::Url::parse("https://site.com").expect("url");
+        let key = master.linking_key(&site).expect("key");
+
+        let k1 = *b"01234567890123456789012345678901";
+        let sig = secp256k1::ecdsa::Signature::from_der(&key.sign(&k1)).expect("der");
+        let pubkey = secp256k1::PublicKey::from_slice(&key.public_key()).expect("pubkey");
+
+        assert!(secp256k1::SECP256K1
+            .verify_ecdsa(&secp256k1::Message::from_digest(k1), &sig, &pubkey)
+            .is_ok());
+    }
+}
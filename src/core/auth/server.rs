@@ -1,18 +1,126 @@
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entrypoint {
+    pub url: url::Url,
+    #[cfg_attr(feature = "serde", serde(with = "hex::serde"))]
+    pub k1: [u8; 32],
+    pub action: Option<super::Action>,
+}
+
+impl Entrypoint {
+    /// Login entrypoint with a fresh random `k1`, `url` being where callbacks are served.
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case randomness is not available.
+    pub fn new(
+        url: url::Url,
+        action: Option<super::Action>,
+    ) -> Result<Self, crate::error::CryptoError> {
+        let mut k1 = [0; 32];
+        getrandom::getrandom(&mut k1)?;
+        Ok(Entrypoint { url, k1, action })
+    }
+
+    /// # Errors
+    ///
+    /// Returns error in case the query cannot be serialized.
+    pub fn url(&self) -> Result<url::Url, crate::error::RenderError> {
+        Ok(crate::core::with_query(&self.url, &self.query()?))
+    }
+
+    /// LUD-17 `keyauth://` link.
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case the query cannot be serialized or `url` is not `https`.
+    pub fn keyauth(&self) -> Result<String, crate::error::RenderError> {
+        crate::lnurl::scheme(&self.url()?, crate::lnurl::Scheme::Auth)
+    }
+
+    /// Bech32 `lnurl1...` link.
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case the query cannot be serialized or encoded.
+    pub fn lnurl(&self) -> Result<String, crate::error::RenderError> {
+        crate::lnurl::encode(&self.url()?)
+    }
+
+    fn query(&self) -> Result<String, serde_urlencoded::ser::Error> {
+        serde_urlencoded::to_string(ser::Entrypoint {
+            tag: super::TAG,
+            k1: &self.k1,
+            action: self.action.map(|a| match a {
+                super::Action::Register => ser::Action::Register,
+                super::Action::Login => ser::Action::Login,
+                super::Action::Link => ser::Action::Link,
+                super::Action::Auth => ser::Action::Auth,
+            }),
+        })
+    }
+}
+
+impl std::fmt::Display for Entrypoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.query().map_err(|_| std::fmt::Error)?)
+    }
+}
+
+/// Authenticated callback, only built after `sig` is verified against `key`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Callback {
-    pub sig: [u8; 64],
-    pub key: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "hex::serde"))]
+    pub k1: [u8; 32],
+    #[cfg_attr(feature = "serde", serde(with = "hex::serde"))]
+    pub key: [u8; 33],
 }
 
 impl<'a> TryFrom<&'a str> for Callback {
-    type Error = &'static str;
+    type Error = crate::error::ParseError;
 
     fn try_from(s: &'a str) -> Result<Self, Self::Error> {
-        serde_urlencoded::from_str::<de::Callback>(s)
-            .map_err(|_| "deserialize failed")
-            .map(|cb| Callback {
-                sig: cb.sig,
-                key: cb.key,
-            })
+        let cb = serde_urlencoded::from_str::<de::Callback>(s)?;
+
+        let key =
+            secp256k1::PublicKey::from_slice(&cb.key).map_err(crate::error::ParseError::Key)?;
+        let mut sig = secp256k1::ecdsa::Signature::from_der_lax(&cb.sig)
+            .or_else(|_| secp256k1::ecdsa::Signature::from_compact(&cb.sig))
+            .map_err(crate::error::ParseError::Signature)?;
+
+        sig.normalize_s();
+
+        secp256k1::SECP256K1
+            .verify_ecdsa(&secp256k1::Message::from_digest(cb.k1), &sig, &key)
+            .map_err(|_| crate::error::ParseError::BadSignature)?;
+
+        Ok(Callback {
+            k1: cb.k1,
+            key: key.serialize(),
+        })
+    }
+}
+
+mod ser {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    pub(super) struct Entrypoint<'a> {
+        pub tag: &'static str,
+        #[serde(with = "hex::serde")]
+        pub k1: &'a [u8; 32],
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub action: Option<Action>,
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "lowercase")]
+    pub(super) enum Action {
+        Register,
+        Login,
+        Link,
+        Auth,
     }
 }
 
@@ -22,7 +130,9 @@ mod de {
     #[derive(Deserialize)]
     pub(super) struct Callback {
         #[serde(with = "hex::serde")]
-        pub sig: [u8; 64],
+        pub k1: [u8; 32],
+        #[serde(with = "hex::serde")]
+        pub sig: Vec<u8>,
         #[serde(with = "hex::serde")]
         pub key: Vec<u8>,
     }
@@ -31,17 +141,135 @@ mod de {
 #[cfg(test)]
 mod tests {
     #[test]
-    fn callback_parse() {
+    fn entrypoint_render() {
+        let entrypoint = super::Entrypoint {
+            url: url::Url::parse("https://site.com/keyauth").expect("url"),
+            k1: *b"oiprazereusouobaites!vamocamigos",
+            action: None,
+        };
+
+        assert_eq!(
+            entrypoint.to_string(),
+            "tag=login&k1=6f697072617a65726575736f756f6261697465732176616d6f63616d69676f73"
+        );
+
+        let entrypoint = super::Entrypoint {
+            action: Some(super::super::Action::Register),
+            ..entrypoint
+        };
+
+        assert_eq!(
+            entrypoint.to_string(),
+            "tag=login&k1=6f697072617a65726575736f756f6261697465732176616d6f63616d69676f73&action=register"
+        );
+
+        assert_eq!(
+            entrypoint.keyauth().expect("keyauth"),
+            "keyauth://site.com/keyauth?tag=login&k1=6f697072617a65726575736f756f6261697465732176616d6f63616d69676f73&action=register"
+        );
+
+        let crate::Resolved::Auth(url, parsed) =
+            crate::resolve(&entrypoint.lnurl().expect("lnurl")).expect("resolve")
+        else {
+            panic!("expected resolved auth");
+        };
+
+        assert_eq!(url, entrypoint.url().expect("url"));
+        assert_eq!(parsed.k1, entrypoint.k1);
+        assert!(matches!(
+            parsed.action,
+            Some(super::super::Action::Register)
+        ));
+    }
+
+    #[test]
+    fn entrypoint_url_fragment() {
+        let entrypoint = super::Entrypoint {
+            url: url::Url::parse("https://site.com/keyauth?s=poon#frag").expect("url"),
+            k1: *b"oiprazereusouobaites!vamocamigos",
+            action: None,
+        };
+
+        assert_eq!(
+            entrypoint.url().expect("url").as_str(),
+            "https://site.com/keyauth?s=poon&tag=login&k1=6f697072617a65726575736f756f6261697465732176616d6f63616d69676f73#frag"
+        );
+    }
+
+    #[test]
+    fn entrypoint_new() {
+        let url = url::Url::parse("https://site.com/keyauth").expect("url");
+        let a = super::Entrypoint::new(url.clone(), None).expect("new");
+        let b = super::Entrypoint::new(url, None).expect("new");
+        assert_ne!(a.k1, b.k1);
+    }
+
+    fn linking_key() -> crate::auth::linking::LinkingKey {
+        let url = url::Url::parse("https://site.com").expect("url");
+        crate::auth::linking::Bip32Master::from_seed(b"oiprazereusouobaites!vamocamigos")
+            .expect("master")
+            .linking_key(&url)
+            .expect("key")
+    }
+
+    #[test]
+    fn callback_parse_der() {
+        let key = linking_key();
+        let k1 = *b"oiprazereusouobaites!vamocamigos";
+
+        let input = format!(
+            "k1={}&sig={}&key={}",
+            hex::encode(k1),
+            hex::encode(key.sign(&k1)),
+            hex::encode(key.public_key())
+        );
+
+        let parsed: super::Callback = input.as_str().try_into().expect("try_into");
+        assert_eq!(parsed.k1, k1);
+        assert_eq!(parsed.key, key.public_key());
+    }
+
+    #[test]
+    fn callback_parse_compact() {
+        let key = linking_key();
+        let k1 = *b"oiprazereusouobaites!vamocamigos";
+        let sig = secp256k1::ecdsa::Signature::from_der(&key.sign(&k1)).expect("der");
+
+        let input = format!(
+            "k1={}&sig={}&key={}",
+            hex::encode(k1),
+            hex::encode(sig.serialize_compact()),
+            hex::encode(key.public_key())
+        );
+
+        let parsed: super::Callback = input.as_str().try_into().expect("try_into");
+        assert_eq!(parsed.key, key.public_key());
+    }
+
+    #[test]
+    fn callback_parse_bad_signature() {
+        let key = linking_key();
+        let k1 = *b"oiprazereusouobaites!vamocamigos";
+
+        let input = format!(
+            "k1={}&sig={}&key={}",
+            hex::encode(b"01234567890123456789012345678901"),
+            hex::encode(key.sign(&k1)),
+            hex::encode(key.public_key())
+        );
+
+        let parsed: Result<super::Callback, _> = input.as_str().try_into();
+        assert!(matches!(
+            parsed,
+            Err(crate::error::ParseError::BadSignature)
+        ));
+
         let input =
             "k1=6f697072617a65726575736f756f6261697465732176616d6f63616d69676f73\
             &sig=30313233343536373839303132333435363738393031323334353637383930313233343536373839303132333435363738393031323334353637383930313233\
             &key=636861766573";
 
-        let parsed: super::Callback = input.try_into().expect("try_into");
-        assert_eq!(parsed.key, b"chaves");
-        assert_eq!(
-            &parsed.sig,
-            b"0123456789012345678901234567890123456789012345678901234567890123"
-        );
+        let parsed: Result<super::Callback, _> = input.try_into();
+        assert!(matches!(parsed, Err(crate::error::ParseError::Key(_))));
     }
 }
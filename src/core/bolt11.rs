@@ -0,0 +1,468 @@
+use secp256k1::hashes::{sha256, Hash};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Network {
+    Bitcoin,
+    Testnet,
+    Signet,
+    Regtest,
+}
+
+/// Decoded BOLT11 invoice, only built after its signature is checked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Invoice {
+    pub network: Network,
+    pub amount: Option<u64>,
+    pub timestamp: u64,
+    pub expiry: u64,
+    #[cfg_attr(feature = "serde", serde(with = "hex::serde"))]
+    pub payment_hash: [u8; 32],
+    pub description: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::core::hex_option"))]
+    pub description_hash: Option<[u8; 32]>,
+    #[cfg_attr(feature = "serde", serde(with = "hex::serde"))]
+    pub payee: [u8; 33],
+}
+
+impl Invoice {
+    /// Unix timestamp after which the invoice should not be paid.
+    #[must_use]
+    pub fn expires_at(&self) -> u64 {
+        self.timestamp.saturating_add(self.expiry)
+    }
+
+    #[must_use]
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at()
+    }
+}
+
+impl std::str::FromStr for Invoice {
+    type Err = crate::error::InvoiceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hrp, data, variant) = bech32::decode(s)?;
+
+        if variant != bech32::Variant::Bech32 {
+            return Err(crate::error::InvoiceError::Malformed("bech32 variant"));
+        }
+
+        let (network, amount) = parse_hrp(&hrp)?;
+
+        if data.len() < TIMESTAMP_LENGTH + SIGNATURE_LENGTH {
+            return Err(crate::error::InvoiceError::Malformed("data"));
+        }
+
+        let (data, signature) = data.split_at(data.len() - SIGNATURE_LENGTH);
+        let timestamp = to_u64(&data[..TIMESTAMP_LENGTH])?;
+
+        let mut payment_hash = None;
+        let mut description = None;
+        let mut description_hash = None;
+        let mut expiry = None;
+        let mut payee = None;
+
+        let mut fields = &data[TIMESTAMP_LENGTH..];
+        while !fields.is_empty() {
+            if fields.len() < 3 {
+                return Err(crate::error::InvoiceError::Malformed("field"));
+            }
+
+            let tag = fields[0].to_u8();
+            let length = usize::from(fields[1].to_u8()) * 32 + usize::from(fields[2].to_u8());
+            let value = fields
+                .get(3..3 + length)
+                .ok_or(crate::error::InvoiceError::Malformed("field"))?;
+            fields = &fields[3 + length..];
+
+            // Fields with unexpected lengths are skipped, as BOLT11 mandates.
+            match (tag, length) {
+                (TAG_PAYMENT_HASH, 52) => payment_hash = Some(to_bytes(value)?),
+                (TAG_DESCRIPTION, _) => {
+                    let bytes = bech32::convert_bits(value, 5, 8, false)
+                        .map_err(|_| crate::error::InvoiceError::Malformed("description"))?;
+                    description = Some(
+                        String::from_utf8(bytes)
+                            .map_err(|_| crate::error::InvoiceError::Malformed("description"))?,
+                    );
+                }
+                (TAG_DESCRIPTION_HASH, 52) => description_hash = Some(to_bytes(value)?),
+                (TAG_EXPIRY, _) => expiry = Some(to_u64(value)?),
+                (TAG_PAYEE, 53) => payee = Some(to_bytes(value)?),
+                _ => {}
+            }
+        }
+
+        let signature = bech32::convert_bits(signature, 5, 8, false)
+            .map_err(|_| crate::error::InvoiceError::Malformed("signature"))?;
+        let recovery_id = secp256k1::ecdsa::RecoveryId::from_i32(i32::from(signature[64]))
+            .map_err(crate::error::InvoiceError::Signature)?;
+        let signature =
+            secp256k1::ecdsa::RecoverableSignature::from_compact(&signature[..64], recovery_id)
+                .map_err(crate::error::InvoiceError::Signature)?;
+
+        let mut preimage = hrp.into_bytes();
+        preimage.extend(
+            bech32::convert_bits(data, 5, 8, true)
+                .map_err(|_| crate::error::InvoiceError::Malformed("data"))?,
+        );
+        let message =
+            secp256k1::Message::from_digest(sha256::Hash::hash(&preimage).to_byte_array());
+
+        let recovered = secp256k1::SECP256K1
+            .recover_ecdsa(&message, &signature)
+            .map_err(|_| crate::error::InvoiceError::BadSignature)?
+            .serialize();
+
+        if matches!(payee, Some(payee) if payee != recovered) {
+            return Err(crate::error::InvoiceError::BadSignature);
+        }
+
+        Ok(Invoice {
+            network,
+            amount,
+            timestamp,
+            expiry: expiry.unwrap_or(DEFAULT_EXPIRY),
+            payment_hash: payment_hash
+                .ok_or(crate::error::InvoiceError::Malformed("payment hash"))?,
+            description,
+            description_hash,
+            payee: recovered,
+        })
+    }
+}
+
+const TIMESTAMP_LENGTH: usize = 7;
+const SIGNATURE_LENGTH: usize = 104;
+const DEFAULT_EXPIRY: u64 = 3600;
+
+const TAG_PAYMENT_HASH: u8 = 1;
+const TAG_EXPIRY: u8 = 6;
+const TAG_DESCRIPTION: u8 = 13;
+const TAG_PAYEE: u8 = 19;
+const TAG_DESCRIPTION_HASH: u8 = 23;
+
+fn parse_hrp(hrp: &str) -> Result<(Network, Option<u64>), crate::error::InvoiceError> {
+    let rest = hrp
+        .strip_prefix("ln")
+        .ok_or(crate::error::InvoiceError::Hrp)?;
+
+    let (network, amount) = if let Some(amount) = rest.strip_prefix("bcrt") {
+        (Network::Regtest, amount)
+    } else if let Some(amount) = rest.strip_prefix("bc") {
+        (Network::Bitcoin, amount)
+    } else if let Some(amount) = rest.strip_prefix("tbs") {
+        (Network::Signet, amount)
+    } else if let Some(amount) = rest.strip_prefix("tb") {
+        (Network::Testnet, amount)
+    } else {
+        return Err(crate::error::InvoiceError::Hrp);
+    };
+
+    if amount.is_empty() {
+        return Ok((network, None));
+    }
+
+    let (digits, multiplier) = match amount.as_bytes()[amount.len() - 1] {
+        m @ (b'm' | b'u' | b'n' | b'p') => (&amount[..amount.len() - 1], Some(m)),
+        _ => (amount, None),
+    };
+
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(crate::error::InvoiceError::Hrp);
+    }
+
+    let value = digits
+        .parse::<u64>()
+        .map_err(|_| crate::error::InvoiceError::Hrp)?;
+
+    let msats = match multiplier {
+        None => value.checked_mul(100_000_000_000),
+        Some(b'm') => value.checked_mul(100_000_000),
+        Some(b'u') => value.checked_mul(100_000),
+        Some(b'n') => value.checked_mul(100),
+        _ if value % 10 == 0 => Some(value / 10),
+        _ => None,
+    };
+
+    Ok((network, Some(msats.ok_or(crate::error::InvoiceError::Hrp)?)))
+}
+
+fn to_u64(data: &[bech32::u5]) -> Result<u64, crate::error::InvoiceError> {
+    if data.len() > 12 {
+        return Err(crate::error::InvoiceError::Malformed("number"));
+    }
+
+    Ok(data
+        .iter()
+        .fold(0, |acc, d| (acc << 5) | u64::from(d.to_u8())))
+}
+
+fn to_bytes<const N: usize>(data: &[bech32::u5]) -> Result<[u8; N], crate::error::InvoiceError> {
+    bech32::convert_bits(data, 5, 8, false)
+        .map_err(|_| crate::error::InvoiceError::Malformed("bytes"))?
+        .try_into()
+        .map_err(|_| crate::error::InvoiceError::Malformed("bytes"))
+}
+
+/// Signs an invoice with the given raw fields, so tests have invoices to check.
+#[cfg(test)]
+pub(crate) fn encode(
+    hrp: &str,
+    timestamp: u64,
+    fields: &[(u8, Vec<bech32::u5>)],
+    key: &secp256k1::SecretKey,
+) -> String {
+    use bech32::ToBase32;
+
+    let mut data = number(timestamp, TIMESTAMP_LENGTH);
+    for (tag, value) in fields {
+        data.push(bech32::u5::try_from_u8(*tag).expect("tag"));
+        data.extend(number(value.len() as u64, 2));
+        data.extend(value);
+    }
+
+    let mut preimage = hrp.as_bytes().to_vec();
+    preimage.extend(bech32::convert_bits(&data, 5, 8, true).expect("bits"));
+    let message = secp256k1::Message::from_digest(sha256::Hash::hash(&preimage).to_byte_array());
+
+    let (id, compact) = secp256k1::SECP256K1
+        .sign_ecdsa_recoverable(&message, key)
+        .serialize_compact();
+
+    let mut signature = compact.to_vec();
+    signature.push(u8::try_from(id.to_i32()).expect("id"));
+    data.extend(signature.to_base32());
+
+    bech32::encode(hrp, data, bech32::Variant::Bech32).expect("encode")
+}
+
+/// Big endian `n` over `length` 5 bit words.
+#[cfg(test)]
+pub(crate) fn number(n: u64, length: usize) -> Vec<bech32::u5> {
+    (0..length)
+        .rev()
+        .map(|i| {
+            bech32::u5::try_from_u8(u8::try_from((n >> (i * 5)) & 31).expect("u5")).expect("u5")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bech32::ToBase32;
+
+    fn key() -> secp256k1::SecretKey {
+        secp256k1::SecretKey::from_slice(b"oiprazereusouobaites!vamocamigos").expect("key")
+    }
+
+    fn fields() -> Vec<(u8, Vec<bech32::u5>)> {
+        vec![
+            (super::TAG_PAYMENT_HASH, [1; 32].to_base32()),
+            (super::TAG_DESCRIPTION, b"verde com bolinhas".to_base32()),
+            (super::TAG_EXPIRY, super::number(60, 2)),
+            (0, b"desconhecido".to_base32()),
+        ]
+    }
+
+    #[test]
+    fn parse() {
+        let input = super::encode("lnbc2500u", 1_496_314_658, &fields(), &key());
+        let parsed: super::Invoice = input.parse().expect("parse");
+
+        assert_eq!(parsed.network, super::Network::Bitcoin);
+        assert_eq!(parsed.amount, Some(250_000_000));
+        assert_eq!(parsed.timestamp, 1_496_314_658);
+        assert_eq!(parsed.expiry, 60);
+        assert_eq!(parsed.payment_hash, [1; 32]);
+        assert_eq!(parsed.description.as_deref(), Some("verde com bolinhas"));
+        assert!(parsed.description_hash.is_none());
+        assert_eq!(
+            parsed.payee,
+            key().public_key(secp256k1::SECP256K1).serialize()
+        );
+        assert!(parsed.is_expired(1_496_314_718));
+        assert!(!parsed.is_expired(1_496_314_717));
+    }
+
+    #[test]
+    fn parse_defaults() {
+        let fields = vec![
+            (super::TAG_PAYMENT_HASH, [1; 32].to_base32()),
+            (super::TAG_DESCRIPTION_HASH, [2; 32].to_base32()),
+        ];
+
+        let input = super::encode("lntbs", 1_496_314_658, &fields, &key());
+        let parsed: super::Invoice = input.parse().expect("parse");
+
+        assert_eq!(parsed.network, super::Network::Signet);
+        assert_eq!(parsed.amount, None);
+        assert_eq!(parsed.expiry, 3600);
+        assert!(parsed.description.is_none());
+        assert_eq!(parsed.description_hash, Some([2; 32]));
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_value(&parsed).expect("serialize");
+            assert_eq!(json["description_hash"], hex::encode([2; 32]));
+
+            let back: super::Invoice = serde_json::from_value(json).expect("deserialize");
+            assert_eq!(back, parsed);
+        }
+    }
+
+    #[test]
+    fn parse_payee() {
+        let payee = key().public_key(secp256k1::SECP256K1).serialize();
+        let mut with_payee = fields();
+        with_payee.push((super::TAG_PAYEE, payee.to_base32()));
+
+        let input = super::encode("lnbc", 1_496_314_658, &with_payee, &key());
+        let parsed: super::Invoice = input.parse().expect("parse");
+        assert_eq!(parsed.payee, payee);
+
+        let other = secp256k1::SecretKey::from_slice(&[3; 32]).expect("key");
+        let input = super::encode("lnbc", 1_496_314_658, &with_payee, &other);
+        assert!(matches!(
+            input.parse::<super::Invoice>(),
+            Err(crate::error::InvoiceError::BadSignature)
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let input = super::encode("lnbc2500u", 1_496_314_658, &fields(), &key());
+        let parsed: super::Invoice = input.parse().expect("parse");
+
+        let json = serde_json::to_value(&parsed).expect("serialize");
+        assert_eq!(
+            json["payee"],
+            hex::encode(key().public_key(secp256k1::SECP256K1).serialize())
+        );
+        assert_eq!(json["payment_hash"], hex::encode([1; 32]));
+        assert!(json["description_hash"].is_null());
+
+        let back: super::Invoice = serde_json::from_value(json).expect("deserialize");
+        assert_eq!(back, parsed);
+    }
+
+    #[test]
+    fn parse_tampered() {
+        let input = super::encode("lnbc2500u", 1_496_314_658, &fields(), &key());
+        let tampered = input.replacen("lnbc2500u", "lnbc2600u", 1);
+        assert!(tampered.parse::<super::Invoice>().is_err());
+
+        let missing = vec![(super::TAG_DESCRIPTION, b"sem hash".to_base32())];
+        let input = super::encode("lnbc", 1_496_314_658, &missing, &key());
+        assert!(matches!(
+            input.parse::<super::Invoice>(),
+            Err(crate::error::InvoiceError::Malformed("payment hash"))
+        ));
+    }
+
+    // Test vectors from BOLT11, all signed by the same node.
+    const SPEC_PAYEE: &str = "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad";
+    const SPEC_PAYMENT_HASH: &str =
+        "0001020304050607080900010203040506070809000102030405060708090102";
+    const SPEC_DESCRIPTION: &str = "One piece of chocolate cake, one icecream cone, one pickle, one slice of swiss cheese, one slice of salami, one lollypop, one piece of cherry pie, one sausage, one cupcake, and one slice of watermelon";
+
+    fn spec(input: &str) -> super::Invoice {
+        let parsed: super::Invoice = input.parse().expect("parse");
+
+        assert_eq!(hex::encode(parsed.payee), SPEC_PAYEE);
+        assert_eq!(hex::encode(parsed.payment_hash), SPEC_PAYMENT_HASH);
+        assert_eq!(parsed.timestamp, 1_496_314_658);
+
+        parsed
+    }
+
+    #[test]
+    fn parse_spec_donation() {
+        let parsed = spec("lnbc1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdpl2pkx2ctnv5sxxmmwwd5kgetjypeh2ursdae8g6twvus8g6rfwvs8qun0dfjkxaq8rkx3yf5tcsyz3d73gafnh3cax9rn449d9p5uxz9ezhhypd0elx87sjle52x86fux2ypatgddc6k63n7erqz25le42c4u4ecky03ylcqca784w");
+
+        assert_eq!(parsed.network, super::Network::Bitcoin);
+        assert_eq!(parsed.amount, None);
+        assert_eq!(
+            parsed.description.as_deref(),
+            Some("Please consider supporting this project")
+        );
+        assert!(parsed.description_hash.is_none());
+    }
+
+    #[test]
+    fn parse_spec_coffee() {
+        let parsed = spec("lnbc2500u1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpuaztrnwngzn3kdzw5hydlzf03qdgm2hdq27cqv3agm2awhz5se903vruatfhq77w3ls4evs3ch9zw97j25emudupq63nyw24cg27h2rspfj9srp");
+
+        assert_eq!(parsed.network, super::Network::Bitcoin);
+        assert_eq!(parsed.amount, Some(250_000_000));
+        assert_eq!(parsed.expiry, 60);
+        assert_eq!(parsed.description.as_deref(), Some("1 cup coffee"));
+        assert!(parsed.description_hash.is_none());
+    }
+
+    #[test]
+    fn parse_spec_hashed() {
+        use secp256k1::hashes::{sha256, Hash};
+
+        let parsed = spec("lnbc20m1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqhp58yjmdan79s6qqdhdzgynm4zwqd5d7xmw5fk98klysy043l2ahrqscc6gd6ql3jrc5yzme8v4ntcewwz5cnw92tz0pc8qcuufvq7khhr8wpald05e92xw006sq94mg8v2ndf4sefvf9sygkshp5zfem29trqq2yxxz7");
+
+        assert_eq!(parsed.network, super::Network::Bitcoin);
+        assert_eq!(parsed.amount, Some(2_000_000_000));
+        assert!(parsed.description.is_none());
+        assert_eq!(
+            parsed.description_hash,
+            Some(sha256::Hash::hash(SPEC_DESCRIPTION.as_bytes()).to_byte_array())
+        );
+    }
+
+    #[test]
+    fn parse_spec_testnet() {
+        use secp256k1::hashes::{sha256, Hash};
+
+        let parsed = spec("lntb20m1pvjluezhp58yjmdan79s6qqdhdzgynm4zwqd5d7xmw5fk98klysy043l2ahrqspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqfpp3x9et2e20v6pu37c5d9vax37wxq72un98kmzzhznpurw9sgl2v0nklu2g4d0keph5t7tj9tcqd8rexnd07ux4uv2cjvcqwaxgj7v4uwn5wmypjd5n69z2xm3xgksg28nwht7f6zspwp3f9t");
+
+        assert_eq!(parsed.network, super::Network::Testnet);
+        assert_eq!(parsed.amount, Some(2_000_000_000));
+        assert!(parsed.description.is_none());
+        assert_eq!(
+            parsed.description_hash,
+            Some(sha256::Hash::hash(SPEC_DESCRIPTION.as_bytes()).to_byte_array())
+        );
+    }
+
+    #[test]
+    fn parse_hrp() {
+        assert_eq!(
+            super::parse_hrp("lnbc").unwrap(),
+            (super::Network::Bitcoin, None)
+        );
+        assert_eq!(
+            super::parse_hrp("lnbc2500u").unwrap(),
+            (super::Network::Bitcoin, Some(250_000_000))
+        );
+        assert_eq!(
+            super::parse_hrp("lnbc20m").unwrap(),
+            (super::Network::Bitcoin, Some(2_000_000_000))
+        );
+        assert_eq!(
+            super::parse_hrp("lntb10n").unwrap(),
+            (super::Network::Testnet, Some(1000))
+        );
+        assert_eq!(
+            super::parse_hrp("lntbs10p").unwrap(),
+            (super::Network::Signet, Some(1))
+        );
+        assert_eq!(
+            super::parse_hrp("lnbcrt1").unwrap(),
+            (super::Network::Regtest, Some(100_000_000_000))
+        );
+
+        assert!(super::parse_hrp("lnbc1p").is_err());
+        assert!(super::parse_hrp("lnbcu").is_err());
+        assert!(super::parse_hrp("lnxx1").is_err());
+        assert!(super::parse_hrp("bc1").is_err());
+    }
+}
@@ -1,20 +1,24 @@
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Entrypoint {
     pub callback: url::Url,
     pub uri: String,
     pub k1: String,
+    /// Top-level fields not covered by any of the above.
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl TryFrom<&[u8]> for Entrypoint {
-    type Error = &'static str;
+    type Error = crate::error::ParseError;
 
     fn try_from(s: &[u8]) -> Result<Self, Self::Error> {
-        let d: de::Entrypoint = serde_json::from_slice(s).map_err(|_| "deserialize failed")?;
+        let d: de::Entrypoint = serde_json::from_slice(s)?;
 
         Ok(Entrypoint {
             callback: d.callback,
             uri: d.uri,
             k1: d.k1,
+            extra: crate::core::extra(d.extra, &["tag"]),
         })
     }
 }
@@ -93,6 +97,7 @@ impl std::fmt::Display for Callback<'_> {
 
 mod de {
     use serde::Deserialize;
+    use serde_json::{Map, Value};
     use url::Url;
 
     #[derive(Deserialize)]
@@ -100,6 +105,8 @@ mod de {
         pub callback: Url,
         pub uri: String,
         pub k1: String,
+        #[serde(flatten)]
+        pub extra: Map<String, Value>,
     }
 }
 
@@ -1,4 +1,5 @@
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Entrypoint {
     pub callback: url::Url,
     pub uri: String,
@@ -6,7 +7,7 @@ pub struct Entrypoint {
 }
 
 impl TryFrom<Entrypoint> for Vec<u8> {
-    type Error = &'static str;
+    type Error = crate::error::RenderError;
 
     fn try_from(r: Entrypoint) -> Result<Self, Self::Error> {
         serde_json::to_vec(&ser::Entrypoint {
@@ -15,11 +16,12 @@ impl TryFrom<Entrypoint> for Vec<u8> {
             uri: &r.uri,
             k1: &r.k1,
         })
-        .map_err(|_| "serialize failed")
+        .map_err(crate::error::RenderError::from)
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Callback {
     Accept {
         k1: String,
@@ -33,11 +35,11 @@ pub enum Callback {
 }
 
 impl<'a> TryFrom<&'a str> for Callback {
-    type Error = &'static str;
+    type Error = crate::error::ParseError;
 
     fn try_from(s: &'a str) -> Result<Self, Self::Error> {
         serde_urlencoded::from_str::<super::serde::Callback>(s)
-            .map_err(|_| "deserialize failed")
+            .map_err(crate::error::ParseError::from)
             .map(|query| match query {
                 super::serde::Callback::Accept {
                     k1,
@@ -0,0 +1,300 @@
+use std::fmt;
+
+/// Failures turning user input into something to be queried, see [`crate::resolve`].
+#[derive(Debug)]
+pub enum ResolveError {
+    Unknown,
+    Bech32(bech32::Error),
+    Hrp(String),
+    NotText(std::string::FromUtf8Error),
+    Url(url::ParseError),
+    Address,
+    Insecure,
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::Unknown => f.write_str("unknown input"),
+            ResolveError::Bech32(_) => f.write_str("bech32 decode failed"),
+            ResolveError::Hrp(hrp) => write!(f, "bech32 hrp invalid: {hrp}"),
+            ResolveError::NotText(_) => f.write_str("bech32 bytes is not string"),
+            ResolveError::Url(_) => f.write_str("bad url"),
+            ResolveError::Address => f.write_str("bad lightning address"),
+            ResolveError::Insecure => f.write_str("clearnet url is not https"),
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ResolveError::Bech32(e) => Some(e),
+            ResolveError::NotText(e) => Some(e),
+            ResolveError::Url(e) => Some(e),
+            ResolveError::Unknown
+            | ResolveError::Hrp(_)
+            | ResolveError::Address
+            | ResolveError::Insecure => None,
+        }
+    }
+}
+
+impl From<bech32::Error> for ResolveError {
+    fn from(e: bech32::Error) -> Self {
+        ResolveError::Bech32(e)
+    }
+}
+
+impl From<url::ParseError> for ResolveError {
+    fn from(e: url::ParseError) -> Self {
+        ResolveError::Url(e)
+    }
+}
+
+/// Failures understanding entrypoints, callbacks and responses.
+#[derive(Debug)]
+pub enum ParseError {
+    Json(serde_json::Error),
+    Query(serde_urlencoded::de::Error),
+    Url(url::ParseError),
+    Hex(hex::FromHexError),
+    UnknownTag(String),
+    Missing(&'static str),
+    Key(secp256k1::Error),
+    Signature(secp256k1::Error),
+    BadSignature,
+    TooLong(&'static str),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Json(_) => f.write_str("deserialize json failed"),
+            ParseError::Query(_) => f.write_str("deserialize query failed"),
+            ParseError::Url(_) => f.write_str("url parse failed"),
+            ParseError::Hex(_) => f.write_str("hex decode failed"),
+            ParseError::UnknownTag(tag) => write!(f, "unknown tag: {tag}"),
+            ParseError::Missing(what) => write!(f, "missing {what}"),
+            ParseError::Key(_) => f.write_str("invalid key"),
+            ParseError::Signature(_) => f.write_str("invalid signature"),
+            ParseError::BadSignature => f.write_str("bad signature"),
+            ParseError::TooLong(what) => write!(f, "{what} too long"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Json(e) => Some(e),
+            ParseError::Query(e) => Some(e),
+            ParseError::Url(e) => Some(e),
+            ParseError::Hex(e) => Some(e),
+            ParseError::Key(e) | ParseError::Signature(e) => Some(e),
+            ParseError::UnknownTag(_)
+            | ParseError::Missing(_)
+            | ParseError::BadSignature
+            | ParseError::TooLong(_) => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for ParseError {
+    fn from(e: serde_json::Error) -> Self {
+        ParseError::Json(e)
+    }
+}
+
+impl From<serde_urlencoded::de::Error> for ParseError {
+    fn from(e: serde_urlencoded::de::Error) -> Self {
+        ParseError::Query(e)
+    }
+}
+
+impl From<url::ParseError> for ParseError {
+    fn from(e: url::ParseError) -> Self {
+        ParseError::Url(e)
+    }
+}
+
+impl From<hex::FromHexError> for ParseError {
+    fn from(e: hex::FromHexError) -> Self {
+        ParseError::Hex(e)
+    }
+}
+
+/// Failures producing entrypoints, responses and links.
+#[derive(Debug)]
+pub enum RenderError {
+    Json(serde_json::Error),
+    Query(serde_urlencoded::ser::Error),
+    Url(url::ParseError),
+    Bech32(bech32::Error),
+    NotHttps,
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::Json(_) => f.write_str("serialize json failed"),
+            RenderError::Query(_) => f.write_str("serialize query failed"),
+            RenderError::Url(_) => f.write_str("url parse failed"),
+            RenderError::Bech32(_) => f.write_str("bech32 encode failed"),
+            RenderError::NotHttps => f.write_str("url is not https"),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RenderError::Json(e) => Some(e),
+            RenderError::Query(e) => Some(e),
+            RenderError::Url(e) => Some(e),
+            RenderError::Bech32(e) => Some(e),
+            RenderError::NotHttps => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for RenderError {
+    fn from(e: serde_json::Error) -> Self {
+        RenderError::Json(e)
+    }
+}
+
+impl From<serde_urlencoded::ser::Error> for RenderError {
+    fn from(e: serde_urlencoded::ser::Error) -> Self {
+        RenderError::Query(e)
+    }
+}
+
+impl From<url::ParseError> for RenderError {
+    fn from(e: url::ParseError) -> Self {
+        RenderError::Url(e)
+    }
+}
+
+/// Failures on keys, randomness and encryption.
+#[derive(Debug)]
+pub enum CryptoError {
+    Random(getrandom::Error),
+    Key(secp256k1::Error),
+    NoHost,
+    NotAes,
+    Decrypt,
+    NotText(std::string::FromUtf8Error),
+    TooLong(&'static str),
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::Random(_) => f.write_str("random failed"),
+            CryptoError::Key(_) => f.write_str("invalid key"),
+            CryptoError::NoHost => f.write_str("url has no host"),
+            CryptoError::NotAes => f.write_str("not aes"),
+            CryptoError::Decrypt => f.write_str("decrypt failed"),
+            CryptoError::NotText(_) => f.write_str("plaintext is not string"),
+            CryptoError::TooLong(what) => write!(f, "{what} too long"),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CryptoError::Random(e) => Some(e),
+            CryptoError::Key(e) => Some(e),
+            CryptoError::NotText(e) => Some(e),
+            CryptoError::NoHost
+            | CryptoError::NotAes
+            | CryptoError::Decrypt
+            | CryptoError::TooLong(_) => None,
+        }
+    }
+}
+
+impl From<getrandom::Error> for CryptoError {
+    fn from(e: getrandom::Error) -> Self {
+        CryptoError::Random(e)
+    }
+}
+
+impl From<secp256k1::Error> for CryptoError {
+    fn from(e: secp256k1::Error) -> Self {
+        CryptoError::Key(e)
+    }
+}
+
+/// Failures decoding BOLT11 invoices or matching them against what was asked for.
+#[derive(Debug)]
+pub enum InvoiceError {
+    Bech32(bech32::Error),
+    Hrp,
+    Malformed(&'static str),
+    Signature(secp256k1::Error),
+    BadSignature,
+    Payer(serde_json::Error),
+    WrongNetwork,
+    WrongAmount,
+    WrongDescriptionHash,
+    Expired,
+}
+
+impl fmt::Display for InvoiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvoiceError::Bech32(_) => f.write_str("bech32 decode failed"),
+            InvoiceError::Hrp => f.write_str("hrp invalid"),
+            InvoiceError::Malformed(what) => write!(f, "malformed {what}"),
+            InvoiceError::Signature(_) => f.write_str("invalid signature"),
+            InvoiceError::BadSignature => f.write_str("bad signature"),
+            InvoiceError::Payer(_) => f.write_str("serialize payer failed"),
+            InvoiceError::WrongNetwork => f.write_str("wrong network"),
+            InvoiceError::WrongAmount => f.write_str("wrong amount"),
+            InvoiceError::WrongDescriptionHash => f.write_str("wrong description hash"),
+            InvoiceError::Expired => f.write_str("expired"),
+        }
+    }
+}
+
+impl std::error::Error for InvoiceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InvoiceError::Bech32(e) => Some(e),
+            InvoiceError::Signature(e) => Some(e),
+            InvoiceError::Payer(e) => Some(e),
+            InvoiceError::Hrp
+            | InvoiceError::Malformed(_)
+            | InvoiceError::BadSignature
+            | InvoiceError::WrongNetwork
+            | InvoiceError::WrongAmount
+            | InvoiceError::WrongDescriptionHash
+            | InvoiceError::Expired => None,
+        }
+    }
+}
+
+impl From<bech32::Error> for InvoiceError {
+    fn from(e: bech32::Error) -> Self {
+        InvoiceError::Bech32(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    #[test]
+    fn sources() {
+        let err = crate::resolve("lnurl1invalid").expect_err("error");
+        assert!(matches!(err, super::ResolveError::Bech32(_)));
+        assert!(err.source().is_some());
+
+        let err = crate::Entrypoint::try_from(br#"{ "tag": "nope" }"# as &[u8]).expect_err("error");
+        assert_eq!(err.to_string(), "unknown tag: nope");
+        assert!(err.source().is_none());
+    }
+}
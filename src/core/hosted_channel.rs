@@ -0,0 +1,3 @@
+pub const TAG: &str = "hostedChannelRequest";
+pub mod client;
+pub mod server;
@@ -0,0 +1,66 @@
+/// There is no HTTP callback on LUD-07: the wallet connects to `uri`
+/// and sends `k1` as the secret of its `invoke_hosted_channel` message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entrypoint {
+    pub uri: String,
+    pub k1: String,
+    pub alias: Option<String>,
+    /// Top-level fields not covered by any of the above.
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl TryFrom<&[u8]> for Entrypoint {
+    type Error = crate::error::ParseError;
+
+    fn try_from(s: &[u8]) -> Result<Self, Self::Error> {
+        let d: de::Entrypoint = serde_json::from_slice(s)?;
+
+        Ok(Entrypoint {
+            uri: d.uri,
+            k1: d.k1,
+            alias: d.alias,
+            extra: crate::core::extra(d.extra, &["tag"]),
+        })
+    }
+}
+
+mod de {
+    use serde::Deserialize;
+    use serde_json::{Map, Value};
+
+    #[derive(Deserialize)]
+    pub(super) struct Entrypoint {
+        pub uri: String,
+        pub k1: String,
+        pub alias: Option<String>,
+        #[serde(flatten)]
+        pub extra: Map<String, Value>,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn entrypoint_parse() {
+        let input = r#"{
+            "uri": "noh@ipe:porta",
+            "k1": "caum",
+            "alias": "apelido"
+        }"#;
+
+        let parsed: super::Entrypoint = input.as_bytes().try_into().expect("parse");
+
+        assert_eq!(parsed.uri, "noh@ipe:porta");
+        assert_eq!(parsed.k1, "caum");
+        assert_eq!(parsed.alias.unwrap(), "apelido");
+
+        let input = r#"{
+            "uri": "noh@ipe:porta",
+            "k1": "caum"
+        }"#;
+
+        let parsed: super::Entrypoint = input.as_bytes().try_into().expect("parse");
+        assert!(parsed.alias.is_none());
+    }
+}
@@ -0,0 +1,57 @@
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entrypoint {
+    pub uri: String,
+    pub k1: String,
+    pub alias: Option<String>,
+}
+
+impl TryFrom<Entrypoint> for Vec<u8> {
+    type Error = crate::error::RenderError;
+
+    fn try_from(r: Entrypoint) -> Result<Self, Self::Error> {
+        serde_json::to_vec(&ser::Entrypoint {
+            tag: super::TAG,
+            uri: &r.uri,
+            k1: &r.k1,
+            alias: r.alias.as_deref(),
+        })
+        .map_err(crate::error::RenderError::from)
+    }
+}
+
+mod ser {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    pub(super) struct Entrypoint<'a> {
+        pub tag: &'static str,
+        pub uri: &'a str,
+        pub k1: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub alias: Option<&'a str>,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn entrypoint_render() {
+        let query = super::Entrypoint {
+            uri: String::from("noh@ipe:porta"),
+            k1: String::from("caum"),
+            alias: Some(String::from("apelido")),
+        };
+
+        let json = br#"{"tag":"hostedChannelRequest","uri":"noh@ipe:porta","k1":"caum","alias":"apelido"}"#;
+        assert_eq!(Vec::<u8>::try_from(query.clone()).unwrap(), json);
+
+        let query = super::Entrypoint {
+            alias: None,
+            ..query
+        };
+
+        let json = br#"{"tag":"hostedChannelRequest","uri":"noh@ipe:porta","k1":"caum"}"#;
+        assert_eq!(Vec::<u8>::try_from(query).unwrap(), json);
+    }
+}
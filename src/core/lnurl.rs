@@ -0,0 +1,131 @@
+/// LUD-17 schemes, one per kind of entrypoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Scheme {
+    Auth,
+    Channel,
+    Pay,
+    Withdraw,
+}
+
+impl Scheme {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scheme::Auth => "keyauth",
+            Scheme::Channel => "lnurlc",
+            Scheme::Pay => "lnurlp",
+            Scheme::Withdraw => "lnurlw",
+        }
+    }
+}
+
+/// Bech32 `lnurl1...` encoding of `url`, which [`crate::resolve`] turns back into `url`.
+///
+/// # Errors
+///
+/// Returns error in case the url cannot be bech32 encoded.
+pub fn encode(url: &url::Url) -> Result<String, crate::error::RenderError> {
+    bech32::encode(
+        "lnurl",
+        bech32::ToBase32::to_base32(&url.as_str()),
+        bech32::Variant::Bech32,
+    )
+    .map_err(crate::error::RenderError::Bech32)
+}
+
+/// Uppercase `LNURL1...` encoding of `url`, which fits QR codes better.
+///
+/// # Errors
+///
+/// Returns error in case the url cannot be bech32 encoded.
+pub fn encode_upper(url: &url::Url) -> Result<String, crate::error::RenderError> {
+    encode(url).map(|s| s.to_ascii_uppercase())
+}
+
+/// LUD-17 link of `url`, as in `lnurlp://site.com/path`.
+///
+/// Only `https` urls, or `http` ones for onion services, are accepted, since that is what
+/// the schemes resolve to.
+///
+/// # Errors
+///
+/// Returns error in case `url` is neither `https` nor an onion `http` one.
+pub fn scheme(url: &url::Url, scheme: Scheme) -> Result<String, crate::error::RenderError> {
+    if !crate::core::is_secure(url) {
+        return Err(crate::error::RenderError::NotHttps);
+    }
+
+    let rest = &url[url::Position::BeforeUsername..];
+    Ok(format!("{}://{rest}", scheme.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn encode_roundtrip() {
+        let url = url::Url::parse("https://there.is/no?s=poon").expect("url");
+
+        let lnurl = super::encode(&url).expect("encode");
+        assert_eq!(
+            lnurl,
+            "lnurl1dp68gurn8ghj7argv4ex2tnfwvhkumelwv7hqmm0dc6p3ztw"
+        );
+
+        let crate::Resolved::Url(resolved) = crate::resolve(&lnurl).expect("resolve") else {
+            panic!("expected resolved url");
+        };
+        assert_eq!(resolved, url);
+
+        let lnurl = super::encode_upper(&url).expect("encode");
+        assert_eq!(
+            lnurl,
+            "LNURL1DP68GURN8GHJ7ARGV4EX2TNFWVHKUMELWV7HQMM0DC6P3ZTW"
+        );
+
+        let crate::Resolved::Url(resolved) = crate::resolve(&lnurl).expect("resolve") else {
+            panic!("expected resolved url");
+        };
+        assert_eq!(resolved, url);
+
+        let url = url::Url::parse("https://lnurlkit.test:8080/lnurlp").expect("url");
+        let crate::Resolved::Url(resolved) =
+            crate::resolve(&super::encode(&url).expect("encode")).expect("resolve")
+        else {
+            panic!("expected resolved url");
+        };
+        assert_eq!(resolved, url);
+    }
+
+    #[test]
+    fn scheme_roundtrip() {
+        let url = url::Url::parse("https://there.is:8443/no?s=poon").expect("url");
+
+        for (scheme, expected) in [
+            (super::Scheme::Auth, "keyauth://there.is:8443/no?s=poon"),
+            (super::Scheme::Channel, "lnurlc://there.is:8443/no?s=poon"),
+            (super::Scheme::Pay, "lnurlp://there.is:8443/no?s=poon"),
+            (super::Scheme::Withdraw, "lnurlw://there.is:8443/no?s=poon"),
+        ] {
+            let link = super::scheme(&url, scheme).expect("scheme");
+            assert_eq!(link, expected);
+
+            let crate::Resolved::Url(resolved) = crate::resolve(&link).expect("resolve") else {
+                panic!("expected resolved url");
+            };
+            assert_eq!(resolved, url);
+        }
+
+        let url = url::Url::parse("http://there.is/no").expect("url");
+        assert!(super::scheme(&url, super::Scheme::Pay).is_err());
+
+        let url = url::Url::parse("http://n0sp00n.onion/no").expect("url");
+        let link = super::scheme(&url, super::Scheme::Pay).expect("scheme");
+        assert_eq!(link, "lnurlp://n0sp00n.onion/no");
+
+        let crate::Resolved::Url(resolved) = crate::resolve(&link).expect("resolve") else {
+            panic!("expected resolved url");
+        };
+        assert_eq!(resolved, url);
+    }
+}
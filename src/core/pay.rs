@@ -2,13 +2,38 @@ pub const TAG: &str = "payRequest";
 pub mod client;
 pub mod server;
 
-#[derive(Clone, Debug)]
+/// LUD-10: limits on `aes` success actions, description in characters and ciphertext as base64
+/// characters.
+const AES_DESCRIPTION_MAX: usize = 144;
+const AES_CIPHERTEXT_MAX: usize = 4096;
+
+/// Whether `ciphertext` stays within [`AES_CIPHERTEXT_MAX`] once base64 encoded, with padding.
+fn aes_ciphertext_fits(ciphertext: &[u8]) -> bool {
+    (ciphertext.len() + 2) / 3 * 4 <= AES_CIPHERTEXT_MAX
+}
+
+/// `sha256(metadata + payerdata)`, which invoices must carry as `description_hash`.
+///
+/// Payer data only takes part when it was sent on the callback (LUD-18).
+#[must_use]
+pub fn description_hash(metadata: &str, payerdata: Option<&str>) -> [u8; 32] {
+    use secp256k1::hashes::{sha256, Hash, HashEngine};
+
+    let mut engine = sha256::Hash::engine();
+    engine.input(metadata.as_bytes());
+    engine.input(payerdata.unwrap_or_default().as_bytes());
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub enum Amount {
     Millisatoshis(u64),
     Currency(String, u64),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub struct Currency {
     pub code: String,
     pub name: String,
@@ -18,13 +43,15 @@ pub struct Currency {
     pub convertible: Option<CurrencyConvertible>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub struct CurrencyConvertible {
     pub min: u64,
     pub max: u64,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub struct PayerRequirements {
     pub name: Option<PayerRequirement>,
     pub pubkey: Option<PayerRequirement>,
@@ -34,31 +61,72 @@ pub struct PayerRequirements {
     pub others: std::collections::HashMap<String, PayerRequirement>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub struct PayerRequirement {
     pub mandatory: bool,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub struct PayerRequirementAuth {
     pub mandatory: bool,
+    #[cfg_attr(feature = "serde", serde(with = "hex::serde"))]
     pub k1: [u8; 32],
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub struct PayerInformations {
     pub name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::core::hex_option"))]
     pub pubkey: Option<Vec<u8>>,
     pub identifier: Option<String>,
     pub email: Option<String>,
     pub auth: Option<PayerInformationAuth>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 pub struct PayerInformationAuth {
+    #[cfg_attr(feature = "serde", serde(with = "hex::serde"))]
     pub key: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "hex::serde"))]
     pub k1: [u8; 32],
-    pub sig: [u8; 64],
+    #[cfg_attr(feature = "serde", serde(with = "hex::serde"))]
+    pub sig: Vec<u8>,
+}
+
+impl PayerInformationAuth {
+    #[must_use]
+    pub fn sign(key: &crate::auth::linking::LinkingKey, k1: [u8; 32]) -> Self {
+        PayerInformationAuth {
+            key: key.public_key().to_vec(),
+            sig: key.sign(&k1),
+            k1,
+        }
+    }
+}
+
+/// LUD-06 metadata, as `(type, content)` entries in the order they were given.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct Metadata(pub Vec<(String, serde_json::Value)>);
+
+impl Metadata {
+    /// Content of the first entry of type `kind`, as in `text/plain`.
+    #[must_use]
+    pub fn get(&self, kind: &str) -> Option<&serde_json::Value> {
+        self.0.iter().find_map(|(k, v)| (k == kind).then_some(v))
+    }
+
+    pub fn push(&mut self, kind: impl Into<String>, content: impl Into<serde_json::Value>) {
+        self.0.push((kind.into(), content.into()));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &serde_json::Value)> {
+        self.0.iter().map(|(k, v)| (k as &str, v))
+    }
 }
 
 mod serde {
@@ -131,7 +199,7 @@ mod serde {
         #[serde(with = "hex::serde")]
         pub k1: [u8; 32],
         #[serde(with = "hex::serde")]
-        pub sig: [u8; 64],
+        pub sig: Vec<u8>,
     }
 
     pub(super) mod amount {
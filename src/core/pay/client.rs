@@ -1,29 +1,36 @@
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Entrypoint {
     pub callback: url::Url,
     pub metadata_raw: String,
+    /// Every metadata entry, including those without a field of their own.
+    pub metadata: super::Metadata,
     pub short_description: String,
     pub long_description: Option<String>,
     pub identifier: Option<String>,
     pub email: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::core::hex_option"))]
     pub jpeg: Option<Vec<u8>>,
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::core::hex_option"))]
     pub png: Option<Vec<u8>>,
     pub comment_size: Option<u64>,
     pub min: u64,
     pub max: u64,
     pub currencies: Option<Vec<super::Currency>>,
     pub payer: Option<super::PayerRequirements>,
+    /// Top-level fields not covered by any of the above.
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 #[allow(clippy::too_many_lines)]
 impl TryFrom<&[u8]> for Entrypoint {
-    type Error = &'static str;
+    type Error = crate::error::ParseError;
 
     fn try_from(s: &[u8]) -> Result<Self, Self::Error> {
         use base64::{prelude::BASE64_STANDARD, Engine};
         use serde_json::Value;
 
-        let p: de::Entrypoint = serde_json::from_slice(s).map_err(|_| "deserialize failed")?;
+        let p: de::Entrypoint = serde_json::from_slice(s)?;
 
         let currencies = p.currencies.map(|cs| {
             cs.into_iter()
@@ -72,57 +79,25 @@ impl TryFrom<&[u8]> for Entrypoint {
                 .collect(),
         });
 
-        let metadata = serde_json::from_str::<Vec<(String, Value)>>(&p.metadata)
-            .map_err(|_| "deserialize metadata failed")?;
+        let metadata = super::Metadata(serde_json::from_str(&p.metadata)?);
 
-        let short_description = metadata
-            .iter()
-            .find_map(|(k, v)| (k == "text/plain").then_some(v))
-            .and_then(|v| match v {
-                Value::String(s) => Some(String::from(s)),
-                _ => None,
-            })
-            .ok_or("short description failed")?;
-
-        let long_description = metadata
-            .iter()
-            .find_map(|(k, v)| (k == "text/long-desc").then_some(v))
-            .and_then(|v| match v {
-                Value::String(s) => Some(String::from(s)),
-                _ => None,
-            });
-
-        let jpeg = metadata
-            .iter()
-            .find_map(|(k, v)| (k == "image/jpeg;base64").then_some(v))
-            .and_then(|v| match v {
-                Value::String(s) => BASE64_STANDARD.decode(s).ok(),
-                _ => None,
-            });
-
-        let png = metadata
-            .iter()
-            .find_map(|(k, v)| (k == "image/png;base64").then_some(v))
-            .and_then(|v| match v {
-                Value::String(s) => BASE64_STANDARD.decode(s).ok(),
-                _ => None,
-            });
+        let text = |kind| match metadata.get(kind) {
+            Some(Value::String(s)) => Some(String::from(s)),
+            _ => None,
+        };
 
-        let identifier = metadata
-            .iter()
-            .find_map(|(k, v)| (k == "text/identifier").then_some(v))
-            .and_then(|v| match v {
-                Value::String(s) => Some(String::from(s)),
-                _ => None,
-            });
+        let image = |kind| match metadata.get(kind) {
+            Some(Value::String(s)) => BASE64_STANDARD.decode(s).ok(),
+            _ => None,
+        };
 
-        let email = metadata
-            .iter()
-            .find_map(|(k, v)| (k == "text/email").then_some(v))
-            .and_then(|v| match v {
-                Value::String(s) => Some(String::from(s)),
-                _ => None,
-            });
+        let short_description =
+            text("text/plain").ok_or(crate::error::ParseError::Missing("text/plain metadata"))?;
+        let long_description = text("text/long-desc");
+        let jpeg = image("image/jpeg;base64");
+        let png = image("image/png;base64");
+        let identifier = text("text/identifier");
+        let email = text("text/email");
 
         Ok(Entrypoint {
             metadata_raw: p.metadata,
@@ -138,6 +113,8 @@ impl TryFrom<&[u8]> for Entrypoint {
             png,
             currencies,
             payer,
+            metadata,
+            extra: crate::core::extra(p.extra, &["tag"]),
         })
     }
 }
@@ -153,6 +130,7 @@ impl Entrypoint {
     ) -> Callback<'a> {
         Callback {
             url: &self.callback,
+            metadata_raw: &self.metadata_raw,
             amount,
             comment,
             convert,
@@ -163,21 +141,63 @@ impl Entrypoint {
 
 pub struct Callback<'a> {
     pub url: &'a url::Url,
+    pub metadata_raw: &'a str,
     pub comment: Option<&'a str>,
     pub amount: &'a super::Amount,
     pub convert: Option<&'a str>,
     pub payer: Option<super::PayerInformations>,
 }
 
-impl std::fmt::Display for Callback<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Callback<'_> {
+    /// Checks the invoice `pr` returned for this callback, as LUD-06 mandates: amount
+    /// (when asked in millisatoshis), description hash over the metadata and payer data
+    /// (LUD-18), `network` and expiry at unix time `now`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case the invoice cannot be decoded or does not match.
+    pub fn verify(
+        &self,
+        pr: &str,
+        network: crate::bolt11::Network,
+        now: u64,
+    ) -> Result<crate::bolt11::Invoice, crate::error::InvoiceError> {
+        let invoice = pr.parse::<crate::bolt11::Invoice>()?;
+
+        if invoice.network != network {
+            return Err(crate::error::InvoiceError::WrongNetwork);
+        }
+
+        if let super::Amount::Millisatoshis(msats) = self.amount {
+            if invoice.amount != Some(*msats) {
+                return Err(crate::error::InvoiceError::WrongAmount);
+            }
+        }
+
+        let payer = self
+            .payerdata()
+            .map_err(crate::error::InvoiceError::Payer)?;
+
+        let hash = super::description_hash(self.metadata_raw, payer.as_deref());
+
+        if invoice.description_hash != Some(hash) {
+            return Err(crate::error::InvoiceError::WrongDescriptionHash);
+        }
+
+        if invoice.is_expired(now) {
+            return Err(crate::error::InvoiceError::Expired);
+        }
+
+        Ok(invoice)
+    }
+
+    fn payerdata(&self) -> Result<Option<String>, serde_json::Error> {
         let pubkey = self
             .payer
             .as_ref()
             .and_then(|p| p.pubkey.as_ref().map(hex::encode));
 
-        let payer = self
-            .payer
+        self.payer
             .as_ref()
             .map(|p| {
                 serde_json::to_string(&super::serde::PayerInformations {
@@ -188,12 +208,17 @@ impl std::fmt::Display for Callback<'_> {
                     auth: p.auth.as_ref().map(|p| super::serde::PayerInformationAuth {
                         key: p.key.clone(),
                         k1: p.k1,
-                        sig: p.sig,
+                        sig: p.sig.clone(),
                     }),
                 })
             })
             .transpose()
-            .map_err(|_| std::fmt::Error)?;
+    }
+}
+
+impl std::fmt::Display for Callback<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let payer = self.payerdata().map_err(|_| std::fmt::Error)?;
 
         let query = ser::Callback {
             comment: self.comment,
@@ -208,24 +233,79 @@ impl std::fmt::Display for Callback<'_> {
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct CallbackResponse {
-    pub pr: String,
-    pub disposable: bool,
-    pub success_action: Option<SuccessAction>,
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CallbackResponse {
+    Error {
+        reason: String,
+        /// Top-level fields besides `status` and `reason`.
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+    Ok {
+        pr: String,
+        disposable: bool,
+        success_action: Option<SuccessAction>,
+        /// Top-level fields not covered by any of the above.
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SuccessAction {
     Url(url::Url, String),
     Message(String),
+    Aes {
+        description: String,
+        #[cfg_attr(feature = "serde", serde(with = "hex::serde"))]
+        ciphertext: Vec<u8>,
+        #[cfg_attr(feature = "serde", serde(with = "hex::serde"))]
+        iv: [u8; 16],
+    },
+}
+
+impl SuccessAction {
+    /// Decrypts an `aes` success action with the preimage of the paid invoice.
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case this is not an `aes` action or decryption fails.
+    pub fn decrypt(&self, preimage: &[u8; 32]) -> Result<String, crate::error::CryptoError> {
+        use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+
+        let SuccessAction::Aes { ciphertext, iv, .. } = self else {
+            return Err(crate::error::CryptoError::NotAes);
+        };
+
+        let plaintext = cbc::Decryptor::<aes::Aes256>::new(preimage.into(), iv.into())
+            .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+            .map_err(|_| crate::error::CryptoError::Decrypt)?;
+
+        String::from_utf8(plaintext).map_err(crate::error::CryptoError::NotText)
+    }
 }
 
 impl std::str::FromStr for CallbackResponse {
-    type Err = &'static str;
+    type Err = crate::error::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let a: de::CallbackResponse = serde_json::from_str(s).map_err(|_| "deserialize failed")?;
+        use base64::{prelude::BASE64_STANDARD, Engine};
+
+        if let Ok(de::CallbackError {
+            status,
+            reason,
+            extra,
+        }) = serde_json::from_str(s)
+        {
+            if status == "ERROR" {
+                return Ok(Self::Error {
+                    reason,
+                    extra: crate::core::extra(extra, &["status", "reason"]),
+                });
+            }
+        }
+
+        let a: de::CallbackResponse = serde_json::from_str(s)?;
 
         let success_action = a
             .success_action
@@ -236,13 +316,41 @@ impl std::str::FromStr for CallbackResponse {
                     let description = String::from(sa.get("description")?);
                     Some(SuccessAction::Url(url, description))
                 }
+                "aes" => {
+                    let description = String::from(sa.get("description")?);
+                    let ciphertext = BASE64_STANDARD.decode(sa.get("ciphertext")?).ok()?;
+                    let iv = BASE64_STANDARD.decode(sa.get("iv")?).ok()?;
+                    let iv = iv.try_into().ok()?;
+
+                    Some(SuccessAction::Aes {
+                        description,
+                        ciphertext,
+                        iv,
+                    })
+                }
                 _ => None,
             });
 
-        Ok(Self {
+        if let Some(SuccessAction::Aes {
+            description,
+            ciphertext,
+            ..
+        }) = &success_action
+        {
+            if description.chars().count() > super::AES_DESCRIPTION_MAX {
+                return Err(crate::error::ParseError::TooLong("aes description"));
+            }
+
+            if !super::aes_ciphertext_fits(ciphertext) {
+                return Err(crate::error::ParseError::TooLong("aes ciphertext"));
+            }
+        }
+
+        Ok(Self::Ok {
             pr: a.pr,
             disposable: a.disposable.unwrap_or(true),
             success_action,
+            extra: crate::core::extra(a.extra, &["status"]),
         })
     }
 }
@@ -263,6 +371,7 @@ mod ser {
 mod de {
     use super::super::serde::{Currency, Payer};
     use serde::Deserialize;
+    use serde_json::{Map, Value};
     use std::collections::BTreeMap;
     use url::Url;
 
@@ -280,6 +389,8 @@ mod de {
         pub currencies: Option<Vec<Currency<'a>>>,
         #[serde(rename = "payerData")]
         pub payer_data: Option<Payer<'a>>,
+        #[serde(flatten)]
+        pub extra: Map<String, Value>,
     }
 
     #[derive(Deserialize)]
@@ -288,6 +399,16 @@ mod de {
         pub disposable: Option<bool>,
         #[serde(rename = "successAction")]
         pub success_action: Option<BTreeMap<String, String>>,
+        #[serde(flatten)]
+        pub extra: Map<String, Value>,
+    }
+
+    #[derive(Deserialize)]
+    pub(super) struct CallbackError {
+        pub status: String,
+        pub reason: String,
+        #[serde(flatten)]
+        pub extra: serde_json::Map<String, serde_json::Value>,
     }
 }
 
@@ -321,6 +442,37 @@ mod tests {
         assert!(parsed.email.is_none());
         assert!(parsed.currencies.is_none());
         assert!(parsed.payer.is_none());
+        assert!(parsed.extra.is_empty());
+    }
+
+    #[test]
+    fn entrypoint_parse_metadata() {
+        let input = r#"{
+            "tag": "payRequest",
+            "callback": "https://yuri?o=callback",
+            "metadata": "[[\"text/tag\", \"cafe\"],[\"text/plain\", \"boneco do steve magal\"],[\"text/crazy\", [1, 2]]]",
+            "maxSendable": 315,
+            "minSendable": 314,
+            "nostrPubkey": "chave",
+            "allowsNostr": true
+        }"#;
+
+        let parsed: super::Entrypoint = input.as_bytes().try_into().expect("parse");
+
+        assert_eq!(parsed.short_description, "boneco do steve magal");
+        assert_eq!(
+            parsed.metadata.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+            ["text/tag", "text/plain", "text/crazy"]
+        );
+        assert_eq!(parsed.metadata.get("text/tag"), Some(&"cafe".into()));
+        assert_eq!(
+            parsed.metadata.get("text/crazy"),
+            Some(&serde_json::json!([1, 2]))
+        );
+
+        assert_eq!(parsed.extra.len(), 2);
+        assert_eq!(parsed.extra["nostrPubkey"], "chave");
+        assert_eq!(parsed.extra["allowsNostr"], true);
     }
 
     #[test]
@@ -497,6 +649,54 @@ mod tests {
         assert_eq!(payer.others.len(), 0);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn entrypoint_serde() {
+        let input = r#"{
+            "callback": "https://yuri?o=callback",
+            "metadata": "[[\"text/plain\", \"boneco do steve magal\"], [\"image/jpeg;base64\", \"AQID\"]]",
+            "maxSendable": 315,
+            "minSendable": 314,
+            "currencies": [
+                { "code": "BRL", "name": "Reais", "symbol": "R$", "decimals": 2, "multiplier": 314.15 }
+            ],
+            "payerData": {
+                "name": { "mandatory": true },
+                "auth": { "mandatory": true, "k1": "3132333132333231333132333132333132333132333132333331323132333132" }
+            }
+        }"#;
+
+        let parsed: super::Entrypoint = input.as_bytes().try_into().expect("parse");
+
+        let json = serde_json::to_value(&parsed).expect("serialize");
+        assert_eq!(json["jpeg"], "010203");
+        assert!(json["png"].is_null());
+        assert_eq!(
+            json["payer"]["auth"]["k1"],
+            "3132333132333231333132333132333132333132333132333331323132333132"
+        );
+
+        let back: super::Entrypoint = serde_json::from_value(json).expect("deserialize");
+        assert_eq!(back, parsed);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn success_action_serde() {
+        let action = super::SuccessAction::Aes {
+            description: String::from("segredo"),
+            ciphertext: vec![1, 2, 3],
+            iv: *b"0123456789012345",
+        };
+
+        let json = serde_json::to_value(&action).expect("serialize");
+        assert_eq!(json["Aes"]["ciphertext"], "010203");
+        assert_eq!(json["Aes"]["iv"], hex::encode(b"0123456789012345"));
+
+        let back: super::SuccessAction = serde_json::from_value(json).expect("deserialize");
+        assert_eq!(back, action);
+    }
+
     #[test]
     fn callback_render_base() {
         let input = r#"{
@@ -631,8 +831,8 @@ mod tests {
                         auth: Some(super::super::PayerInformationAuth {
                             key: b"chave".to_vec(),
                             k1: *b"12332112312313123213123123211322",
-                            sig:
-                                *b"6564565465464564565465464565465464565464565465465465465464654343"
+                            sig: b"6564565465464564565465464565465464565464565465465465465464654343"
+                                .to_vec()
                         })
                     })
                 )
@@ -641,25 +841,150 @@ mod tests {
         );
     }
 
+    #[test]
+    fn callback_verify() {
+        use bech32::ToBase32;
+        use secp256k1::hashes::{sha256, Hash};
+
+        let input = r#"{
+            "metadata": "[[\"text/plain\", \"boneco do steve magal\"]]",
+            "callback": "https://yuri?o=callback",
+            "maxSendable": 315,
+            "minSendable": 314
+        }"#;
+
+        let parsed: super::Entrypoint = input.as_bytes().try_into().expect("parse");
+        let key =
+            secp256k1::SecretKey::from_slice(b"oiprazereusouobaites!vamocamigos").expect("key");
+
+        let invoice = |hrp: &str, description: &[u8]| {
+            let fields = vec![
+                (1, [1; 32].to_base32()),
+                (
+                    23,
+                    sha256::Hash::hash(description).to_byte_array().to_base32(),
+                ),
+            ];
+            crate::bolt11::encode(hrp, 1_700_000_000, &fields, &key)
+        };
+
+        let amount = super::super::Amount::Millisatoshis(3140);
+        let callback = parsed.invoice(&amount, None, None, None);
+        let network = crate::bolt11::Network::Bitcoin;
+        let metadata = parsed.metadata_raw.as_bytes();
+
+        let pr = invoice("lnbc31400p", metadata);
+        let verified = callback
+            .verify(&pr, network, 1_700_000_000)
+            .expect("verify");
+        assert_eq!(verified.amount, Some(3140));
+
+        assert!(matches!(
+            callback.verify(&pr, crate::bolt11::Network::Testnet, 1_700_000_000),
+            Err(crate::error::InvoiceError::WrongNetwork)
+        ));
+
+        assert!(matches!(
+            callback.verify(&pr, network, 1_700_003_600),
+            Err(crate::error::InvoiceError::Expired)
+        ));
+
+        assert!(matches!(
+            callback.verify(&invoice("lnbc31410p", metadata), network, 1_700_000_000),
+            Err(crate::error::InvoiceError::WrongAmount)
+        ));
+
+        assert!(matches!(
+            callback.verify(&invoice("lnbc31400p", b"outra"), network, 1_700_000_000),
+            Err(crate::error::InvoiceError::WrongDescriptionHash)
+        ));
+
+        let payer = super::super::PayerInformations {
+            name: Some(String::from("robson")),
+            pubkey: None,
+            identifier: None,
+            email: None,
+            auth: None,
+        };
+        let callback = parsed.invoice(&amount, None, None, Some(payer));
+
+        let description = [metadata, br#"{"name":"robson"}"#].concat();
+        let pr = invoice("lnbc31400p", &description);
+        assert!(callback.verify(&pr, network, 1_700_000_000).is_ok());
+
+        let amount = super::super::Amount::Currency(String::from("BRL"), 314);
+        let callback = parsed.invoice(&amount, None, None, None);
+        let pr = invoice("lnbc31400p", metadata);
+        assert!(callback.verify(&pr, network, 1_700_000_000).is_ok());
+    }
+
     #[test]
     fn callback_response_parse_base() {
         let input = r#"{ "pr": "pierre" }"#;
 
-        let parsed = input.parse::<super::CallbackResponse>().expect("parse");
-        assert!(parsed.success_action.is_none());
-        assert_eq!(parsed.pr, "pierre");
-        assert!(parsed.disposable);
+        let super::CallbackResponse::Ok {
+            pr,
+            disposable,
+            success_action,
+            extra,
+        } = input.parse().expect("parse")
+        else {
+            panic!("bad callback response");
+        };
+
+        assert!(success_action.is_none());
+        assert_eq!(pr, "pierre");
+        assert!(disposable);
+        assert!(extra.is_empty());
+    }
+
+    #[test]
+    fn callback_response_parse_extra() {
+        let input = r#"{ "status": "OK", "pr": "pierre", "routes": [], "verify": "https://v" }"#;
+
+        let super::CallbackResponse::Ok { extra, .. } = input.parse().expect("parse") else {
+            panic!("bad callback response");
+        };
+
+        assert_eq!(extra.len(), 2);
+        assert_eq!(extra["routes"], serde_json::json!([]));
+        assert_eq!(extra["verify"], "https://v");
+    }
+
+    #[test]
+    fn callback_response_parse_error() {
+        let input = r#"{ "status": "ERROR", "reason": "valor muito baixo", "min": 1000 }"#;
+
+        let super::CallbackResponse::Error { reason, extra } = input.parse().expect("parse") else {
+            panic!("bad callback response");
+        };
+
+        assert_eq!(reason, "valor muito baixo");
+        assert_eq!(extra.len(), 1);
+        assert_eq!(extra["min"], 1000);
     }
 
     #[test]
     fn callback_response_parse_disposable() {
         let input = r#"{ "pr": "", "disposable": true }"#;
         let parsed = input.parse::<super::CallbackResponse>().expect("parse");
-        assert!(parsed.disposable);
+        assert!(matches!(
+            parsed,
+            super::CallbackResponse::Ok {
+                disposable: true,
+                ..
+            }
+        ));
 
         let input = r#"{ "pr": "", "disposable": false }"#;
         let parsed = input.parse::<super::CallbackResponse>().expect("parse");
-        assert!(!parsed.disposable);
+        assert!(matches!(
+            parsed,
+            super::CallbackResponse::Ok {
+                disposable: false,
+                ..
+            }
+        ));
     }
 
     #[test]
@@ -667,9 +992,11 @@ mod tests {
         let input =
             r#"{ "pr": "", "successAction": { "tag": "message", "message": "obrigado!" } }"#;
 
-        let parsed = input.parse::<super::CallbackResponse>().expect("parse");
+        let Ok(super::CallbackResponse::Ok { success_action, .. }) = input.parse() else {
+            panic!("bad callback response");
+        };
 
-        let Some(super::SuccessAction::Message(m)) = parsed.success_action else {
+        let Some(super::SuccessAction::Message(m)) = success_action else {
             panic!("bad success action");
         };
 
@@ -679,13 +1006,70 @@ mod tests {
             { "pr": "", "successAction": { "tag": "url", "description": "valeu demais", "url": "http://eh.nois" } }
         "#;
 
-        let parsed = input.parse::<super::CallbackResponse>().expect("parse");
+        let Ok(super::CallbackResponse::Ok { success_action, .. }) = input.parse() else {
+            panic!("bad callback response");
+        };
 
-        let Some(super::SuccessAction::Url(u, d)) = parsed.success_action else {
+        let Some(super::SuccessAction::Url(u, d)) = success_action else {
             panic!("bad success action");
         };
 
         assert_eq!(u.to_string(), "http://eh.nois/");
         assert_eq!(d, "valeu demais");
+
+        let input = r#"
+            { "pr": "", "successAction": { "tag": "aes", "description": "segredo", "ciphertext": "jDFc8ZwYJnpos60MJdt7sA==", "iv": "MDEyMzQ1Njc4OTAxMjM0NQ==" } }
+        "#;
+
+        let Ok(super::CallbackResponse::Ok { success_action, .. }) = input.parse() else {
+            panic!("bad callback response");
+        };
+
+        let sa = success_action.expect("success action");
+
+        let super::SuccessAction::Aes {
+            description, iv, ..
+        } = &sa
+        else {
+            panic!("bad success action");
+        };
+
+        assert_eq!(description, "segredo");
+        assert_eq!(iv, b"0123456789012345");
+        assert_eq!(
+            sa.decrypt(b"oiprazereusouobaites!vamocamigos").unwrap(),
+            "segredo brutal"
+        );
+        assert!(sa.decrypt(b"01234567890123456789012345678901").is_err());
+    }
+
+    #[test]
+    fn callback_response_parse_aes_limits() {
+        use base64::{prelude::BASE64_STANDARD, Engine};
+
+        let input = |description: &str, ciphertext: &[u8]| {
+            format!(
+                r#"{{ "pr": "", "successAction": {{ "tag": "aes", "description": "{description}", "ciphertext": "{}", "iv": "MDEyMzQ1Njc4OTAxMjM0NQ==" }} }}"#,
+                BASE64_STANDARD.encode(ciphertext)
+            )
+        };
+
+        assert_eq!(BASE64_STANDARD.encode([0; 3072]).len(), 4096);
+        assert_eq!(BASE64_STANDARD.encode([0; 3075]).len(), 4100);
+
+        let parsed = input(&"é".repeat(144), &[0; 3072]).parse::<super::CallbackResponse>();
+        assert!(parsed.is_ok());
+
+        let parsed = input(&"é".repeat(145), &[0; 16]).parse::<super::CallbackResponse>();
+        assert!(matches!(
+            parsed,
+            Err(crate::error::ParseError::TooLong("aes description"))
+        ));
+
+        let parsed = input("segredo", &[0; 3075]).parse::<super::CallbackResponse>();
+        assert!(matches!(
+            parsed,
+            Err(crate::error::ParseError::TooLong("aes ciphertext"))
+        ));
     }
 }
@@ -1,47 +1,79 @@
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Entrypoint {
     pub callback: url::Url,
     pub short_description: String,
     pub long_description: Option<String>,
     pub identifier: Option<String>,
     pub email: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::core::hex_option"))]
     pub jpeg: Option<Vec<u8>>,
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::core::hex_option"))]
     pub png: Option<Vec<u8>>,
     pub comment_size: Option<u64>,
     pub min: u64,
     pub max: u64,
     pub currencies: Option<Vec<super::Currency>>,
     pub payer: Option<super::PayerRequirements>,
+    /// Custom metadata entries, rendered after the ones above.
+    pub extra_metadata: super::Metadata,
 }
 
-impl TryFrom<Entrypoint> for Vec<u8> {
-    type Error = &'static str;
-
-    fn try_from(r: Entrypoint) -> Result<Self, Self::Error> {
+impl Entrypoint {
+    /// Metadata exactly as rendered in the entrypoint, deterministic for the same entrypoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case the metadata cannot be serialized.
+    pub fn metadata(&self) -> Result<String, crate::error::RenderError> {
         use base64::{prelude::BASE64_STANDARD, Engine};
+        use serde_json::Value;
 
-        let metadata = serde_json::to_string(
+        serde_json::to_string(
             &[
-                Some(("text/plain", r.short_description.clone())),
-                r.long_description
-                    .as_ref()
-                    .map(|s| ("text/long-desc", s.clone())),
-                r.jpeg
-                    .as_ref()
-                    .map(|s| ("image/jpeg;base64", BASE64_STANDARD.encode(s))),
-                r.png
+                Some(("text/plain", Value::from(&self.short_description as &str))),
+                self.long_description
+                    .as_deref()
+                    .map(|s| ("text/long-desc", Value::from(s))),
+                self.jpeg
                     .as_ref()
-                    .map(|s| ("image/png;base64", BASE64_STANDARD.encode(s))),
-                r.identifier
+                    .map(|s| ("image/jpeg;base64", Value::from(BASE64_STANDARD.encode(s)))),
+                self.png
                     .as_ref()
-                    .map(|s| ("text/identifier", s.clone())),
-                r.email.as_ref().map(|s| ("text/email", s.clone())),
+                    .map(|s| ("image/png;base64", Value::from(BASE64_STANDARD.encode(s)))),
+                self.identifier
+                    .as_deref()
+                    .map(|s| ("text/identifier", Value::from(s))),
+                self.email
+                    .as_deref()
+                    .map(|s| ("text/email", Value::from(s))),
             ]
             .into_iter()
             .flatten()
+            .chain(self.extra_metadata.iter().map(|(k, v)| (k, v.clone())))
             .collect::<Vec<_>>(),
         )
-        .map_err(|_| "serialize failed")?;
+        .map_err(crate::error::RenderError::from)
+    }
+
+    /// Hash to be used as the invoice `description_hash`, see [`super::description_hash`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case the metadata cannot be serialized.
+    pub fn description_hash(
+        &self,
+        payerdata: Option<&str>,
+    ) -> Result<[u8; 32], crate::error::RenderError> {
+        Ok(super::description_hash(&self.metadata()?, payerdata))
+    }
+}
+
+impl TryFrom<Entrypoint> for Vec<u8> {
+    type Error = crate::error::RenderError;
+
+    fn try_from(r: Entrypoint) -> Result<Self, Self::Error> {
+        let metadata = r.metadata()?;
 
         serde_json::to_vec(&ser::Entrypoint {
             tag: super::TAG,
@@ -101,23 +133,27 @@ impl TryFrom<Entrypoint> for Vec<u8> {
                     .collect(),
             }),
         })
-        .map_err(|_| "serialize failed")
+        .map_err(crate::error::RenderError::from)
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Callback {
     pub amount: super::Amount,
     pub comment: Option<String>,
     pub convert: Option<String>,
     pub payer: Option<super::PayerInformations>,
+    /// Payer data exactly as received, which goes into [`Entrypoint::description_hash`].
+    pub payer_raw: Option<String>,
 }
 
 impl<'a> TryFrom<&'a str> for Callback {
-    type Error = &'static str;
+    type Error = crate::error::ParseError;
 
     fn try_from(s: &'a str) -> Result<Self, Self::Error> {
         serde_urlencoded::from_str::<de::Callback>(s)
-            .map_err(|_| "deserialize failed")
+            .map_err(crate::error::ParseError::from)
             .and_then(|cb| {
                 Ok(Callback {
                     amount: cb.amount,
@@ -125,17 +161,14 @@ impl<'a> TryFrom<&'a str> for Callback {
                     convert: cb.convert.map(String::from),
                     payer: cb
                         .payerdata
+                        .as_deref()
                         .map(|pd| {
-                            serde_json::from_str::<super::serde::PayerInformations>(&pd)
-                                .map_err(|_| "deserialize payer failed")
+                            serde_json::from_str::<super::serde::PayerInformations>(pd)
+                                .map_err(crate::error::ParseError::from)
                                 .and_then(|pi| {
                                     Ok(super::PayerInformations {
                                         name: pi.name.map(String::from),
-                                        pubkey: pi
-                                            .pubkey
-                                            .map(hex::decode)
-                                            .transpose()
-                                            .map_err(|_| "deserialize pubkey failed")?,
+                                        pubkey: pi.pubkey.map(hex::decode).transpose()?,
                                         identifier: pi.identifier.map(String::from),
                                         email: pi.email.map(String::from),
                                         auth: pi.auth.map(|pia| super::PayerInformationAuth {
@@ -147,38 +180,122 @@ impl<'a> TryFrom<&'a str> for Callback {
                                 })
                         })
                         .transpose()?,
+                    payer_raw: cb.payerdata,
                 })
             })
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct CallbackResponse {
-    pub pr: String,
-    pub disposable: bool,
-    pub success_action: Option<SuccessAction>,
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CallbackResponse {
+    Error {
+        reason: String,
+        /// Top-level fields besides `status` and `reason`.
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
+    Ok {
+        pr: String,
+        disposable: bool,
+        success_action: Option<SuccessAction>,
+        /// Top-level fields besides `status` and the ones covered by any of the above.
+        extra: serde_json::Map<String, serde_json::Value>,
+    },
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SuccessAction {
     Url(url::Url, String),
     Message(String),
+    Aes {
+        description: String,
+        #[cfg_attr(feature = "serde", serde(with = "hex::serde"))]
+        ciphertext: Vec<u8>,
+        #[cfg_attr(feature = "serde", serde(with = "hex::serde"))]
+        iv: [u8; 16],
+    },
+}
+
+impl SuccessAction {
+    /// Encrypts `plaintext` with AES-256-CBC under the invoice `preimage` and a random iv.
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case randomness is not available, or the description or ciphertext
+    /// exceed LUD-10 limits.
+    pub fn encrypt(
+        description: String,
+        plaintext: &str,
+        preimage: &[u8; 32],
+    ) -> Result<Self, crate::error::CryptoError> {
+        use aes::cipher::{block_padding::Pkcs7, BlockEncryptMut, KeyIvInit};
+
+        if description.chars().count() > super::AES_DESCRIPTION_MAX {
+            return Err(crate::error::CryptoError::TooLong("description"));
+        }
+
+        let mut iv = [0; 16];
+        getrandom::getrandom(&mut iv)?;
+
+        let ciphertext = cbc::Encryptor::<aes::Aes256>::new(preimage.into(), &iv.into())
+            .encrypt_padded_vec_mut::<Pkcs7>(plaintext.as_bytes());
+
+        if !super::aes_ciphertext_fits(&ciphertext) {
+            return Err(crate::error::CryptoError::TooLong("ciphertext"));
+        }
+
+        Ok(SuccessAction::Aes {
+            description,
+            ciphertext,
+            iv,
+        })
+    }
 }
 
 impl std::fmt::Display for CallbackResponse {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let success_action = self.success_action.as_ref().map(|sa| {
+        use base64::{prelude::BASE64_STANDARD, Engine};
+
+        let (pr, disposable, success_action, extra) = match self {
+            CallbackResponse::Error { reason, extra } => {
+                let cr = ser::CallbackError {
+                    status: "ERROR",
+                    reason,
+                    extra: crate::core::extra(extra.clone(), &["status", "reason"]),
+                };
+                return f.write_str(&serde_json::to_string(&cr).map_err(|_| std::fmt::Error)?);
+            }
+            CallbackResponse::Ok {
+                pr,
+                disposable,
+                success_action,
+                extra,
+            } => (pr, *disposable, success_action, extra),
+        };
+
+        let success_action = success_action.as_ref().map(|sa| {
             let mut map = std::collections::BTreeMap::new();
 
             match sa {
                 SuccessAction::Message(m) => {
-                    map.insert("tag", "message");
-                    map.insert("message", m);
+                    map.insert("tag", String::from("message"));
+                    map.insert("message", m.clone());
                 }
                 SuccessAction::Url(u, d) => {
-                    map.insert("tag", "url");
-                    map.insert("description", d);
-                    map.insert("url", u.as_str());
+                    map.insert("tag", String::from("url"));
+                    map.insert("description", d.clone());
+                    map.insert("url", String::from(u.as_str()));
+                }
+                SuccessAction::Aes {
+                    description,
+                    ciphertext,
+                    iv,
+                } => {
+                    map.insert("tag", String::from("aes"));
+                    map.insert("description", description.clone());
+                    map.insert("ciphertext", BASE64_STANDARD.encode(ciphertext));
+                    map.insert("iv", BASE64_STANDARD.encode(iv));
                 }
             }
 
@@ -186,9 +303,13 @@ impl std::fmt::Display for CallbackResponse {
         });
 
         let cr = ser::CallbackResponse {
+            pr,
+            disposable,
             success_action,
-            disposable: self.disposable,
-            pr: &self.pr,
+            extra: crate::core::extra(
+                extra.clone(),
+                &["status", "pr", "disposable", "successAction"],
+            ),
         };
 
         f.write_str(&serde_json::to_string(&cr).map_err(|_| std::fmt::Error)?)
@@ -223,7 +344,17 @@ mod ser {
         pub pr: &'a str,
         pub disposable: bool,
         #[serde(rename = "successAction")]
-        pub success_action: Option<BTreeMap<&'static str, &'a str>>,
+        pub success_action: Option<BTreeMap<&'static str, String>>,
+        #[serde(flatten)]
+        pub extra: serde_json::Map<String, serde_json::Value>,
+    }
+
+    #[derive(Serialize)]
+    pub(super) struct CallbackError<'a> {
+        pub status: &'static str,
+        pub reason: &'a str,
+        #[serde(flatten)]
+        pub extra: serde_json::Map<String, serde_json::Value>,
     }
 }
 
@@ -257,6 +388,7 @@ mod tests {
             email: None,
             currencies: None,
             payer: None,
+            extra_metadata: super::super::Metadata::default(),
         };
 
         assert_eq!(
@@ -265,6 +397,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn entrypoint_metadata() {
+        use secp256k1::hashes::{sha256, Hash};
+
+        let query = super::Entrypoint {
+            callback: url::Url::parse("https://yuri?o=callback").expect("url"),
+            short_description: String::from("boneco do steve magal"),
+            long_description: Some(String::from("mochila colorida")),
+            jpeg: None,
+            png: None,
+            comment_size: None,
+            min: 314,
+            max: 315,
+            identifier: None,
+            email: Some(String::from("steve@magal")),
+            currencies: None,
+            payer: None,
+            extra_metadata: super::super::Metadata::default(),
+        };
+
+        let metadata = query.metadata().expect("metadata");
+        assert_eq!(
+            metadata,
+            r#"[["text/plain","boneco do steve magal"],["text/long-desc","mochila colorida"],["text/email","steve@magal"]]"#
+        );
+
+        assert_eq!(
+            query.description_hash(None).expect("hash"),
+            sha256::Hash::hash(metadata.as_bytes()).to_byte_array()
+        );
+
+        assert_eq!(
+            query.description_hash(Some("{}")).expect("hash"),
+            sha256::Hash::hash(format!("{metadata}{{}}").as_bytes()).to_byte_array()
+        );
+
+        let bytes = Vec::<u8>::try_from(query).expect("render");
+        let parsed: crate::pay::client::Entrypoint = (&bytes as &[u8]).try_into().expect("parse");
+        assert_eq!(parsed.metadata_raw, metadata);
+    }
+
     #[test]
     fn entrypoint_render_comment_size() {
         let query = super::Entrypoint {
@@ -280,6 +453,7 @@ mod tests {
             email: None,
             currencies: None,
             payer: None,
+            extra_metadata: super::super::Metadata::default(),
         };
 
         assert_eq!(
@@ -303,6 +477,7 @@ mod tests {
             email: None,
             currencies: None,
             payer: None,
+            extra_metadata: super::super::Metadata::default(),
         };
 
         assert_eq!(
@@ -326,6 +501,7 @@ mod tests {
             email: None,
             currencies: None,
             payer: None,
+            extra_metadata: super::super::Metadata::default(),
         };
 
         assert_eq!(
@@ -349,6 +525,7 @@ mod tests {
             email: None,
             currencies: None,
             payer: None,
+            extra_metadata: super::super::Metadata::default(),
         };
 
         assert_eq!(
@@ -372,6 +549,7 @@ mod tests {
             email: Some(String::from("steve@magal.brutal")),
             currencies: None,
             payer: None,
+            extra_metadata: super::super::Metadata::default(),
         };
 
         assert_eq!(
@@ -380,6 +558,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn entrypoint_render_extra_metadata() {
+        let mut extra_metadata = super::super::Metadata::default();
+        extra_metadata.push("text/tag", "cafe");
+        extra_metadata.push("text/crazy", serde_json::json!([1, 2]));
+
+        let query = super::Entrypoint {
+            callback: url::Url::parse("https://yuri?o=callback").expect("url"),
+            short_description: String::from("boneco do steve magal"),
+            long_description: None,
+            jpeg: None,
+            png: None,
+            comment_size: None,
+            min: 314,
+            max: 315,
+            identifier: None,
+            email: Some(String::from("steve@magal.brutal")),
+            currencies: None,
+            payer: None,
+            extra_metadata,
+        };
+
+        assert_eq!(
+            Vec::<u8>::try_from(query).unwrap(),
+            br#"{"tag":"payRequest","metadata":"[[\"text/plain\",\"boneco do steve magal\"],[\"text/email\",\"steve@magal.brutal\"],[\"text/tag\",\"cafe\"],[\"text/crazy\",[1,2]]]","callback":"https://yuri/?o=callback","minSendable":314,"maxSendable":315,"commentAllowed":0}"#
+        );
+    }
+
     #[test]
     fn entrypoint_render_currencies() {
         let query = super::Entrypoint {
@@ -412,6 +618,7 @@ mod tests {
                 },
             ]),
             payer: None,
+            extra_metadata: super::super::Metadata::default(),
         };
 
         assert_eq!(
@@ -450,6 +657,7 @@ mod tests {
                 .into_iter()
                 .collect(),
             }),
+            extra_metadata: super::super::Metadata::default(),
         };
 
         assert_eq!(
@@ -477,6 +685,7 @@ mod tests {
                 auth: None,
                 others: std::collections::HashMap::new(),
             }),
+            extra_metadata: super::super::Metadata::default(),
         };
 
         assert_eq!(
@@ -544,16 +753,75 @@ mod tests {
         assert_eq!(auth.k1, *b"12332112312313123213123123211322");
         assert_eq!(
             auth.sig,
-            *b"6564565465464564565465464565465464565464565465465465465464654343"
+            b"6564565465464564565465464565465464565464565465465465465464654343"
         );
+        assert!(parsed
+            .payer_raw
+            .unwrap()
+            .starts_with(r#"{"name":"robson","#));
+    }
+
+    #[test]
+    fn callback_description_hash() {
+        use bech32::ToBase32;
+
+        let entrypoint = super::Entrypoint {
+            callback: url::Url::parse("https://yuri/callback").expect("url"),
+            short_description: String::from("boneco do steve magal"),
+            long_description: None,
+            jpeg: None,
+            png: None,
+            comment_size: None,
+            min: 314,
+            max: 315,
+            identifier: None,
+            email: None,
+            currencies: None,
+            payer: None,
+            extra_metadata: super::super::Metadata::default(),
+        };
+
+        let bytes = Vec::<u8>::try_from(entrypoint.clone()).expect("render");
+        let client: crate::pay::client::Entrypoint = (&bytes as &[u8]).try_into().expect("parse");
+
+        let amount = crate::pay::Amount::Millisatoshis(3140);
+        let payer = crate::pay::PayerInformations {
+            name: Some(String::from("robson")),
+            pubkey: None,
+            identifier: None,
+            email: None,
+            auth: None,
+        };
+
+        let request = client.invoice(&amount, None, None, Some(payer));
+        let query = request.to_string();
+        let parsed: super::Callback = query
+            .split_once('?')
+            .expect("query")
+            .1
+            .try_into()
+            .expect("parse");
+
+        let hash = entrypoint
+            .description_hash(parsed.payer_raw.as_deref())
+            .expect("hash");
+
+        let key = secp256k1::SecretKey::from_slice(&[1; 32]).expect("key");
+        let fields = vec![(1, [1; 32].to_base32()), (23, hash.to_base32())];
+        let pr = crate::bolt11::encode("lnbc31400p", 1_700_000_000, &fields, &key);
+
+        assert!(request
+            .verify(&pr, crate::bolt11::Network::Bitcoin, 1_700_000_000)
+            .is_ok());
     }
 
     #[test]
     fn callback_response_render_base() {
-        let input = super::CallbackResponse {
+        let input = super::CallbackResponse::Ok {
             pr: String::from("pierre"),
             success_action: None,
             disposable: true,
+            extra: serde_json::Map::new(),
         };
 
         assert_eq!(
@@ -562,12 +830,75 @@ mod tests {
         );
     }
 
+    #[test]
+    fn callback_response_render_error() {
+        let input = super::CallbackResponse::Error {
+            reason: String::from("valor muito baixo"),
+            extra: serde_json::Map::new(),
+        };
+
+        assert_eq!(
+            input.to_string(),
+            r#"{"status":"ERROR","reason":"valor muito baixo"}"#
+        );
+    }
+
+    #[test]
+    fn callback_response_render_extra() {
+        let mut extra = serde_json::Map::new();
+        extra.insert(String::from("routes"), serde_json::json!([]));
+        extra.insert(String::from("pr"), serde_json::Value::from("outro"));
+        extra.insert(String::from("status"), serde_json::Value::from("ERROR"));
+
+        let input = super::CallbackResponse::Ok {
+            pr: String::from("pierre"),
+            success_action: None,
+            disposable: true,
+            extra,
+        };
+
+        let crate::pay::client::CallbackResponse::Ok { pr, extra, .. } =
+            input.to_string().parse().expect("parse")
+        else {
+            panic!("expected ok");
+        };
+
+        assert_eq!(pr, "pierre");
+        assert_eq!(extra.len(), 1);
+        assert_eq!(extra["routes"], serde_json::json!([]));
+
+        let mut extra = serde_json::Map::new();
+        extra.insert(String::from("retry"), serde_json::Value::from(true));
+        extra.insert(String::from("reason"), serde_json::Value::from("outra"));
+
+        let input = super::CallbackResponse::Error {
+            reason: String::from("valor muito baixo"),
+            extra,
+        };
+
+        assert_eq!(
+            input.to_string(),
+            r#"{"status":"ERROR","reason":"valor muito baixo","retry":true}"#
+        );
+
+        let crate::pay::client::CallbackResponse::Error { reason, extra } =
+            input.to_string().parse().expect("parse")
+        else {
+            panic!("expected error");
+        };
+
+        assert_eq!(reason, "valor muito baixo");
+        assert_eq!(extra.len(), 1);
+        assert_eq!(extra["retry"], true);
+    }
+
     #[test]
     fn callback_response_render_disposable() {
-        let input = super::CallbackResponse {
+        let input = super::CallbackResponse::Ok {
             pr: String::from("pierre"),
             success_action: None,
             disposable: false,
+            extra: serde_json::Map::new(),
         };
 
         assert_eq!(
@@ -578,10 +909,11 @@ mod tests {
 
     #[test]
     fn callback_response_render_success_actions() {
-        let input = super::CallbackResponse {
+        let input = super::CallbackResponse::Ok {
             pr: String::from("pierre"),
             success_action: Some(super::SuccessAction::Message(String::from("obrigado!"))),
             disposable: false,
+            extra: serde_json::Map::new(),
         };
 
         assert_eq!(
@@ -589,18 +921,85 @@ mod tests {
             r#"{"pr":"pierre","disposable":false,"successAction":{"message":"obrigado!","tag":"message"}}"#
         );
 
-        let input = super::CallbackResponse {
+        let input = super::CallbackResponse::Ok {
             pr: String::from("pierre"),
             success_action: Some(super::SuccessAction::Url(
                 url::Url::parse("http://recibo").expect("url"),
                 String::from("segue recibo"),
             )),
             disposable: false,
+            extra: serde_json::Map::new(),
         };
 
         assert_eq!(
             input.to_string(),
             r#"{"pr":"pierre","disposable":false,"successAction":{"description":"segue recibo","tag":"url","url":"http://recibo/"}}"#
         );
+
+        let input = super::CallbackResponse::Ok {
+            pr: String::from("pierre"),
+            success_action: Some(super::SuccessAction::Aes {
+                description: String::from("segue segredo"),
+                ciphertext: b"cifrado".to_vec(),
+                iv: *b"0123456789012345",
+            }),
+            disposable: false,
+            extra: serde_json::Map::new(),
+        };
+
+        assert_eq!(
+            input.to_string(),
+            r#"{"pr":"pierre","disposable":false,"successAction":{"ciphertext":"Y2lmcmFkbw==","description":"segue segredo","iv":"MDEyMzQ1Njc4OTAxMjM0NQ==","tag":"aes"}}"#
+        );
+    }
+
+    #[test]
+    fn success_action_encrypt() {
+        use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+
+        let preimage = b"oiprazereusouobaites!vamocamigos";
+        let sa = super::SuccessAction::encrypt(String::from("segue"), "segredo brutal", preimage)
+            .expect("encrypt");
+
+        let super::SuccessAction::Aes {
+            description,
+            ciphertext,
+            iv,
+        } = sa
+        else {
+            panic!("bad success action");
+        };
+
+        let plaintext = cbc::Decryptor::<aes::Aes256>::new(preimage.into(), &iv.into())
+            .decrypt_padded_vec_mut::<Pkcs7>(&ciphertext)
+            .expect("decrypt");
+
+        assert_eq!(description, "segue");
+        assert_eq!(plaintext, b"segredo brutal");
+    }
+
+    #[test]
+    fn success_action_encrypt_limits() {
+        let preimage = b"oiprazereusouobaites!vamocamigos";
+
+        let description = "é".repeat(144);
+        assert!(super::SuccessAction::encrypt(description, "segredo", preimage).is_ok());
+
+        let description = "é".repeat(145);
+        assert!(matches!(
+            super::SuccessAction::encrypt(description, "segredo", preimage),
+            Err(crate::error::CryptoError::TooLong("description"))
+        ));
+
+        // Padded to 3072 bytes of ciphertext, exactly 4096 base64 characters.
+        let plaintext = "a".repeat(3071);
+        assert!(super::SuccessAction::encrypt(String::new(), &plaintext, preimage).is_ok());
+
+        // Padded to 3088 bytes of ciphertext, 4120 base64 characters.
+        let plaintext = "a".repeat(3072);
+        assert!(matches!(
+            super::SuccessAction::encrypt(String::new(), &plaintext, preimage),
+            Err(crate::error::CryptoError::TooLong("ciphertext"))
+        ));
     }
 }
@@ -1,13 +1,3 @@
 pub const TAG: &str = "withdrawRequest";
 pub mod client;
 pub mod server;
-
-mod serde {
-    use serde::{Deserialize, Serialize};
-
-    #[derive(Deserialize, Serialize)]
-    pub(super) struct Callback<'a> {
-        pub k1: &'a str,
-        pub pr: &'a str,
-    }
-}
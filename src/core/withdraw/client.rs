@@ -1,17 +1,22 @@
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Entrypoint {
     pub k1: String,
     pub callback: url::Url,
     pub description: String,
     pub min: u64,
     pub max: u64,
+    pub balance_check: Option<url::Url>,
+    pub pay_link: Option<String>,
+    /// Top-level fields not covered by any of the above.
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl TryFrom<&[u8]> for Entrypoint {
-    type Error = &'static str;
+    type Error = crate::error::ParseError;
 
     fn try_from(s: &[u8]) -> Result<Self, Self::Error> {
-        let d: de::Entrypoint = serde_json::from_slice(s).map_err(|_| "deserialize failed")?;
+        let d: de::Entrypoint = serde_json::from_slice(s)?;
 
         Ok(Entrypoint {
             k1: d.k1,
@@ -19,18 +24,23 @@ impl TryFrom<&[u8]> for Entrypoint {
             description: d.default_description,
             min: d.min_withdrawable,
             max: d.max_withdrawable,
+            balance_check: d.balance_check,
+            pay_link: d.pay_link,
+            extra: crate::core::extra(d.extra, &["tag"]),
         })
     }
 }
 
 impl TryFrom<&str> for Entrypoint {
-    type Error = &'static str;
+    type Error = crate::error::ParseError;
 
     fn try_from(s: &str) -> Result<Self, Self::Error> {
-        let url = url::Url::parse(s).map_err(|_| "url parse failed")?;
-        let query = url.query().ok_or("missing query")?;
+        let url = url::Url::parse(s)?;
+        let query = url
+            .query()
+            .ok_or(crate::error::ParseError::Missing("query"))?;
         let d = serde_urlencoded::from_str::<de::Entrypoint>(query)
-            .map_err(|_| "deserialize failed")?;
+            .map_err(crate::error::ParseError::from)?;
 
         Ok(Entrypoint {
             k1: d.k1,
@@ -38,17 +48,22 @@ impl TryFrom<&str> for Entrypoint {
             description: d.default_description,
             min: d.min_withdrawable,
             max: d.max_withdrawable,
+            balance_check: d.balance_check,
+            pay_link: d.pay_link,
+            extra: crate::core::extra(d.extra, &["tag"]),
         })
     }
 }
 
 impl Entrypoint {
+    /// `balance_notify` being where the service may notify about balance changes (LUD-15).
     #[must_use]
-    pub fn submit<'a>(&'a self, pr: &'a str) -> Callback {
+    pub fn submit<'a>(&'a self, pr: &'a str, balance_notify: Option<&'a url::Url>) -> Callback<'a> {
         Callback {
             url: &self.callback,
             k1: &self.k1,
             pr,
+            balance_notify,
         }
     }
 }
@@ -58,13 +73,15 @@ pub struct Callback<'a> {
     pub url: &'a url::Url,
     pub k1: &'a str,
     pub pr: &'a str,
+    pub balance_notify: Option<&'a url::Url>,
 }
 
 impl std::fmt::Display for Callback<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let query = super::serde::Callback {
+        let query = ser::Callback {
             k1: self.k1,
             pr: self.pr,
+            balance_notify: self.balance_notify,
         };
 
         let querystr = serde_urlencoded::to_string(query).map_err(|_| std::fmt::Error)?;
@@ -73,8 +90,22 @@ impl std::fmt::Display for Callback<'_> {
     }
 }
 
+mod ser {
+    use serde::Serialize;
+    use url::Url;
+
+    #[derive(Serialize)]
+    pub(super) struct Callback<'a> {
+        pub k1: &'a str,
+        pub pr: &'a str,
+        #[serde(rename = "balanceNotify", skip_serializing_if = "Option::is_none")]
+        pub balance_notify: Option<&'a Url>,
+    }
+}
+
 mod de {
     use serde::Deserialize;
+    use serde_json::{Map, Value};
     use url::Url;
 
     #[derive(Deserialize)]
@@ -87,6 +118,12 @@ mod de {
         pub min_withdrawable: u64,
         #[serde(rename = "maxWithdrawable")]
         pub max_withdrawable: u64,
+        #[serde(rename = "balanceCheck")]
+        pub balance_check: Option<Url>,
+        #[serde(rename = "payLink")]
+        pub pay_link: Option<String>,
+        #[serde(flatten)]
+        pub extra: Map<String, Value>,
     }
 }
 
@@ -109,6 +146,58 @@ mod tests {
         assert_eq!(parsed.k1, "caum");
         assert_eq!(parsed.max, 315);
         assert_eq!(parsed.min, 314);
+        assert!(parsed.balance_check.is_none());
+        assert!(parsed.pay_link.is_none());
+    }
+
+    #[test]
+    fn entrypoint_bytes_parse_extra() {
+        let input = r#"{
+            "tag": "withdrawRequest",
+            "callback": "https://yuri?o=callback",
+            "defaultDescription": "verde com bolinhas",
+            "minWithdrawable": 314,
+            "maxWithdrawable": 315,
+            "k1": "caum",
+            "currency": "BRL"
+        }"#;
+
+        let parsed: super::Entrypoint = input.as_bytes().try_into().expect("parse");
+        assert_eq!(parsed.extra.len(), 1);
+        assert_eq!(parsed.extra["currency"], "BRL");
+    }
+
+    #[test]
+    fn entrypoint_bytes_parse_pay_link() {
+        let input = r#"{
+            "callback": "https://yuri?o=callback",
+            "defaultDescription": "verde com bolinhas",
+            "minWithdrawable": 314,
+            "maxWithdrawable": 315,
+            "k1": "caum",
+            "payLink": "lnurlp://yuri/pay"
+        }"#;
+
+        let parsed: super::Entrypoint = input.as_bytes().try_into().expect("parse");
+        assert_eq!(parsed.pay_link.unwrap(), "lnurlp://yuri/pay");
+    }
+
+    #[test]
+    fn entrypoint_bytes_parse_balance_check() {
+        let input = r#"{
+            "callback": "https://yuri?o=callback",
+            "defaultDescription": "verde com bolinhas",
+            "minWithdrawable": 314,
+            "maxWithdrawable": 315,
+            "k1": "caum",
+            "balanceCheck": "https://yuri?o=balance"
+        }"#;
+
+        let parsed: super::Entrypoint = input.as_bytes().try_into().expect("parse");
+        assert_eq!(
+            parsed.balance_check.unwrap().to_string(),
+            "https://yuri/?o=balance"
+        );
     }
 
     #[test]
@@ -127,6 +216,7 @@ mod tests {
         assert_eq!(parsed.k1, "caum");
         assert_eq!(parsed.min, 314);
         assert_eq!(parsed.max, 315);
+        assert!(parsed.balance_check.is_none());
     }
 
     #[test]
@@ -142,8 +232,15 @@ mod tests {
         let parsed: super::Entrypoint = input.as_bytes().try_into().expect("parse");
 
         assert_eq!(
-            parsed.submit("pierre").to_string(),
+            parsed.submit("pierre", None).to_string(),
             "https://yuri/?o=callback&k1=caum&pr=pierre"
         );
+
+        let notify = url::Url::parse("https://yuri?o=notify").expect("url");
+
+        assert_eq!(
+            parsed.submit("pierre", Some(&notify)).to_string(),
+            "https://yuri/?o=callback&k1=caum&pr=pierre&balanceNotify=https%3A%2F%2Fyuri%2F%3Fo%3Dnotify"
+        );
     }
 }
@@ -1,14 +1,59 @@
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Entrypoint {
     pub k1: String,
     pub callback: url::Url,
     pub description: String,
     pub min: u64,
     pub max: u64,
+    pub balance_check: Option<url::Url>,
+    pub pay_link: Option<String>,
+}
+
+impl Entrypoint {
+    /// LUD-08 fast withdraw link, with the entrypoint carried in the query of `base`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case the query cannot be serialized.
+    pub fn url(&self, base: &url::Url) -> Result<url::Url, crate::error::RenderError> {
+        Ok(crate::core::with_query(base, &self.query()?))
+    }
+
+    /// LUD-17 `lnurlw://` fast withdraw link, see [`Entrypoint::url`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case the query cannot be serialized or `base` is not `https`.
+    pub fn lnurlw(&self, base: &url::Url) -> Result<String, crate::error::RenderError> {
+        crate::lnurl::scheme(&self.url(base)?, crate::lnurl::Scheme::Withdraw)
+    }
+
+    /// Bech32 `lnurl1...` fast withdraw link, see [`Entrypoint::url`].
+    ///
+    /// # Errors
+    ///
+    /// Returns error in case the query cannot be serialized or encoded.
+    pub fn lnurl(&self, base: &url::Url) -> Result<String, crate::error::RenderError> {
+        crate::lnurl::encode(&self.url(base)?)
+    }
+
+    fn query(&self) -> Result<String, serde_urlencoded::ser::Error> {
+        serde_urlencoded::to_string(ser::Entrypoint {
+            tag: super::TAG,
+            callback: &self.callback,
+            default_description: &self.description,
+            min_withdrawable: self.min,
+            max_withdrawable: self.max,
+            k1: &self.k1,
+            balance_check: self.balance_check.as_ref(),
+            pay_link: self.pay_link.as_deref(),
+        })
+    }
 }
 
 impl TryFrom<Entrypoint> for Vec<u8> {
-    type Error = &'static str;
+    type Error = crate::error::RenderError;
 
     fn try_from(r: Entrypoint) -> Result<Self, Self::Error> {
         serde_json::to_vec(&ser::Entrypoint {
@@ -18,40 +63,37 @@ impl TryFrom<Entrypoint> for Vec<u8> {
             min_withdrawable: r.min,
             max_withdrawable: r.max,
             k1: &r.k1,
+            balance_check: r.balance_check.as_ref(),
+            pay_link: r.pay_link.as_deref(),
         })
-        .map_err(|_| "serialize failed")
+        .map_err(crate::error::RenderError::from)
     }
 }
 
 impl std::fmt::Display for Entrypoint {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = serde_urlencoded::to_string(ser::Entrypoint {
-            tag: super::TAG,
-            callback: &self.callback,
-            default_description: &self.description,
-            min_withdrawable: self.min,
-            max_withdrawable: self.max,
-            k1: &self.k1,
-        })
-        .map_err(|_| std::fmt::Error)?;
-        f.write_str(&s)
+        f.write_str(&self.query().map_err(|_| std::fmt::Error)?)
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Callback {
     pub k1: String,
     pub pr: String,
+    pub balance_notify: Option<url::Url>,
 }
 
 impl<'a> TryFrom<&'a str> for Callback {
-    type Error = &'static str;
+    type Error = crate::error::ParseError;
 
     fn try_from(s: &'a str) -> Result<Self, Self::Error> {
-        serde_urlencoded::from_str::<super::serde::Callback>(s)
-            .map_err(|_| "deserialize failed")
+        serde_urlencoded::from_str::<de::Callback>(s)
+            .map_err(crate::error::ParseError::from)
             .map(|query| Callback {
-                k1: String::from(query.k1),
-                pr: String::from(query.pr),
+                k1: query.k1,
+                pr: query.pr,
+                balance_notify: query.balance_notify,
             })
     }
 }
@@ -71,11 +113,91 @@ mod ser {
         pub min_withdrawable: u64,
         #[serde(rename = "maxWithdrawable")]
         pub max_withdrawable: u64,
+        #[serde(rename = "balanceCheck", skip_serializing_if = "Option::is_none")]
+        pub balance_check: Option<&'a Url>,
+        #[serde(rename = "payLink", skip_serializing_if = "Option::is_none")]
+        pub pay_link: Option<&'a str>,
+    }
+}
+
+mod de {
+    use serde::Deserialize;
+    use url::Url;
+
+    #[derive(Deserialize)]
+    pub(super) struct Callback {
+        pub k1: String,
+        pub pr: String,
+        #[serde(rename = "balanceNotify")]
+        pub balance_notify: Option<Url>,
     }
 }
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn entrypoint_url() {
+        let query = super::Entrypoint {
+            callback: url::Url::parse("https://yuri?o=callback").expect("url"),
+            description: String::from("verde com bolinhas"),
+            k1: String::from("caum"),
+            min: 314,
+            max: 315,
+            balance_check: None,
+            pay_link: None,
+        };
+
+        let base = url::Url::parse("https://yuri/lnurlw").expect("url");
+        let url = query.url(&base).expect("url");
+
+        let crate::Resolved::Withdraw(resolved, parsed) =
+            crate::resolve(&crate::lnurl::encode(&url).expect("encode")).expect("resolve")
+        else {
+            panic!("expected resolved withdraw");
+        };
+
+        assert_eq!(resolved, url);
+        assert_eq!(parsed.k1, "caum");
+        assert_eq!(parsed.min, 314);
+        assert_eq!(parsed.max, 315);
+        assert_eq!(parsed.description, "verde com bolinhas");
+    }
+
+    #[test]
+    fn entrypoint_url_fragment() {
+        let query = super::Entrypoint {
+            callback: url::Url::parse("https://yuri?o=callback").expect("url"),
+            description: String::new(),
+            k1: String::from("caum"),
+            min: 314,
+            max: 315,
+            balance_check: None,
+            pay_link: None,
+        };
+
+        let base = url::Url::parse("https://yuri/lnurlw?a=b#frag").expect("url");
+        let url = query.url(&base).expect("url");
+
+        assert_eq!(url.fragment(), Some("frag"));
+        assert!(url
+            .query()
+            .expect("query")
+            .starts_with("a=b&tag=withdrawRequest&"));
+
+        let lnurlw = query.lnurlw(&base).expect("lnurlw");
+        assert!(lnurlw.starts_with("lnurlw://yuri/lnurlw?a=b&tag=withdrawRequest&"));
+        assert!(lnurlw.ends_with("#frag"));
+
+        let crate::Resolved::Withdraw(resolved, parsed) =
+            crate::resolve(&query.lnurl(&base).expect("lnurl")).expect("resolve")
+        else {
+            panic!("expected resolved withdraw");
+        };
+
+        assert_eq!(resolved, url);
+        assert_eq!(parsed.k1, "caum");
+    }
+
     #[test]
     fn entrypoint_render() {
         let query = super::Entrypoint {
@@ -84,12 +206,35 @@ mod tests {
             k1: String::from("caum"),
             min: 314,
             max: 315,
+            balance_check: None,
+            pay_link: None,
         };
 
         assert_eq!(
-            Vec::<u8>::try_from(query).unwrap(),
+            Vec::<u8>::try_from(query.clone()).unwrap(),
             br#"{"tag":"withdrawRequest","k1":"caum","callback":"https://yuri/?o=callback","defaultDescription":"verde com bolinhas","minWithdrawable":314,"maxWithdrawable":315}"#
         );
+
+        let query = super::Entrypoint {
+            balance_check: Some(url::Url::parse("https://yuri?o=balance").expect("url")),
+            ..query
+        };
+
+        assert_eq!(
+            Vec::<u8>::try_from(query.clone()).unwrap(),
+            br#"{"tag":"withdrawRequest","k1":"caum","callback":"https://yuri/?o=callback","defaultDescription":"verde com bolinhas","minWithdrawable":314,"maxWithdrawable":315,"balanceCheck":"https://yuri/?o=balance"}"#
+        );
+
+        let query = super::Entrypoint {
+            balance_check: None,
+            pay_link: Some(String::from("lnurlp://yuri/pay")),
+            ..query
+        };
+
+        assert_eq!(
+            Vec::<u8>::try_from(query).unwrap(),
+            br#"{"tag":"withdrawRequest","k1":"caum","callback":"https://yuri/?o=callback","defaultDescription":"verde com bolinhas","minWithdrawable":314,"maxWithdrawable":315,"payLink":"lnurlp://yuri/pay"}"#
+        );
     }
 
     #[test]
@@ -99,5 +244,18 @@ mod tests {
 
         assert_eq!(parsed.pr, "pierre");
         assert_eq!(parsed.k1, "caum");
+        assert!(parsed.balance_notify.is_none());
+    }
+
+    #[test]
+    fn callback_parse_balance_notify() {
+        let input = "k1=caum&pr=pierre&balanceNotify=https%3A%2F%2Fyuri%2F%3Fo%3Dnotify";
+        let parsed: super::Callback = input.try_into().expect("parse");
+
+        assert_eq!(parsed.pr, "pierre");
+        assert_eq!(
+            parsed.balance_notify.unwrap().to_string(),
+            "https://yuri/?o=notify"
+        );
     }
 }
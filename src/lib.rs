@@ -1,9 +1,12 @@
 #![cfg_attr(all(doc, docsrs), feature(doc_auto_cfg))]
 
 mod core;
-pub use core::{auth, channel, pay, resolve, withdraw, CallbackResponse, Entrypoint, Resolved};
+pub use core::{
+    auth, bolt11, channel, error, hosted_channel, lnurl, pay, resolve, resolve_wrapped, withdraw,
+    CallbackResponse, Entrypoint, Resolved, Wrapper,
+};
 
-#[cfg(feature = "client")]
+#[cfg(feature = "client-core")]
 pub mod client;
 
 #[cfg(feature = "client")]
@@ -1,19 +1,23 @@
 use axum::{
     extract::{Path, RawQuery},
     http::StatusCode,
+    response::{IntoResponse, Response},
     routing::get,
     Router,
 };
 use std::future::Future;
 
-pub struct Server<AR, CE, CC, PE, PC, WE, WC> {
+pub struct Server<AR, CE, CC, HE, PE, PC, WE, WC, WB, WN> {
     auth_request: AR,
     channel_entrypoint: CE,
     channel_callback: CC,
+    hosted_channel_entrypoint: HE,
     pay_entrypoint: PE,
     pay_callback: PC,
     withdraw_entrypoint: WE,
     withdraw_callback: WC,
+    withdraw_balance_check: WB,
+    withdraw_balance_notify: WN,
 }
 
 impl Default
@@ -23,12 +27,16 @@ impl Default
         // Channel Request
         unimplemented::Handler<(), crate::channel::server::Entrypoint>,
         unimplemented::Handler<crate::channel::server::Callback, crate::CallbackResponse>,
+        // Hosted Channel Request
+        unimplemented::Handler<(), crate::hosted_channel::server::Entrypoint>,
         // Pay Request
         unimplemented::Handler<Option<String>, crate::pay::server::Entrypoint>,
         unimplemented::Handler<crate::pay::server::Callback, crate::pay::server::CallbackResponse>,
         // Withdraw Request
         unimplemented::Handler<(), crate::withdraw::server::Entrypoint>,
         unimplemented::Handler<crate::withdraw::server::Callback, crate::CallbackResponse>,
+        unimplemented::Handler<String, crate::withdraw::server::Entrypoint>,
+        unimplemented::Ignore<(String, url::Url)>,
     >
 {
     fn default() -> Self {
@@ -38,25 +46,32 @@ impl Default
             channel_entrypoint: unimplemented::handler,
             channel_callback: unimplemented::handler,
 
+            hosted_channel_entrypoint: unimplemented::handler,
+
             pay_entrypoint: unimplemented::handler,
             pay_callback: unimplemented::handler,
 
             withdraw_entrypoint: unimplemented::handler,
             withdraw_callback: unimplemented::handler,
+            withdraw_balance_check: unimplemented::handler,
+            withdraw_balance_notify: unimplemented::ignore,
         }
     }
 }
 
-impl<AR, CE, CC, PE, PC, WE, WC> Server<AR, CE, CC, PE, PC, WE, WC> {
-    pub fn auth<AR2>(self, auth_request: AR2) -> Server<AR2, CE, CC, PE, PC, WE, WC> {
+impl<AR, CE, CC, HE, PE, PC, WE, WC, WB, WN> Server<AR, CE, CC, HE, PE, PC, WE, WC, WB, WN> {
+    pub fn auth<AR2>(self, auth_request: AR2) -> Server<AR2, CE, CC, HE, PE, PC, WE, WC, WB, WN> {
         Server {
             auth_request,
             channel_entrypoint: self.channel_entrypoint,
             channel_callback: self.channel_callback,
+            hosted_channel_entrypoint: self.hosted_channel_entrypoint,
             pay_entrypoint: self.pay_entrypoint,
             pay_callback: self.pay_callback,
             withdraw_entrypoint: self.withdraw_entrypoint,
             withdraw_callback: self.withdraw_callback,
+            withdraw_balance_check: self.withdraw_balance_check,
+            withdraw_balance_notify: self.withdraw_balance_notify,
         }
     }
 
@@ -64,15 +79,36 @@ impl<AR, CE, CC, PE, PC, WE, WC> Server<AR, CE, CC, PE, PC, WE, WC> {
         self,
         channel_entrypoint: CE2,
         channel_callback: CC2,
-    ) -> Server<AR, CE2, CC2, PE, PC, WE, WC> {
+    ) -> Server<AR, CE2, CC2, HE, PE, PC, WE, WC, WB, WN> {
         Server {
             auth_request: self.auth_request,
             channel_entrypoint,
             channel_callback,
+            hosted_channel_entrypoint: self.hosted_channel_entrypoint,
+            pay_entrypoint: self.pay_entrypoint,
+            pay_callback: self.pay_callback,
+            withdraw_entrypoint: self.withdraw_entrypoint,
+            withdraw_callback: self.withdraw_callback,
+            withdraw_balance_check: self.withdraw_balance_check,
+            withdraw_balance_notify: self.withdraw_balance_notify,
+        }
+    }
+
+    pub fn hosted_channel_request<HE2>(
+        self,
+        hosted_channel_entrypoint: HE2,
+    ) -> Server<AR, CE, CC, HE2, PE, PC, WE, WC, WB, WN> {
+        Server {
+            auth_request: self.auth_request,
+            channel_entrypoint: self.channel_entrypoint,
+            channel_callback: self.channel_callback,
+            hosted_channel_entrypoint,
             pay_entrypoint: self.pay_entrypoint,
             pay_callback: self.pay_callback,
             withdraw_entrypoint: self.withdraw_entrypoint,
             withdraw_callback: self.withdraw_callback,
+            withdraw_balance_check: self.withdraw_balance_check,
+            withdraw_balance_notify: self.withdraw_balance_notify,
         }
     }
 
@@ -80,15 +116,18 @@ impl<AR, CE, CC, PE, PC, WE, WC> Server<AR, CE, CC, PE, PC, WE, WC> {
         self,
         pay_entrypoint: PE2,
         pay_callback: PC2,
-    ) -> Server<AR, CE, CC, PE2, PC2, WE, WC> {
+    ) -> Server<AR, CE, CC, HE, PE2, PC2, WE, WC, WB, WN> {
         Server {
             auth_request: self.auth_request,
             channel_entrypoint: self.channel_entrypoint,
             channel_callback: self.channel_callback,
+            hosted_channel_entrypoint: self.hosted_channel_entrypoint,
             pay_entrypoint,
             pay_callback,
             withdraw_entrypoint: self.withdraw_entrypoint,
             withdraw_callback: self.withdraw_callback,
+            withdraw_balance_check: self.withdraw_balance_check,
+            withdraw_balance_notify: self.withdraw_balance_notify,
         }
     }
 
@@ -96,42 +135,111 @@ impl<AR, CE, CC, PE, PC, WE, WC> Server<AR, CE, CC, PE, PC, WE, WC> {
         self,
         withdraw_entrypoint: WE2,
         withdraw_callback: WC2,
-    ) -> Server<AR, CE, CC, PE, PC, WE2, WC2> {
+    ) -> Server<AR, CE, CC, HE, PE, PC, WE2, WC2, WB, WN> {
         Server {
             auth_request: self.auth_request,
             channel_entrypoint: self.channel_entrypoint,
             channel_callback: self.channel_callback,
+            hosted_channel_entrypoint: self.hosted_channel_entrypoint,
             pay_entrypoint: self.pay_entrypoint,
             pay_callback: self.pay_callback,
             withdraw_entrypoint,
             withdraw_callback,
+            withdraw_balance_check: self.withdraw_balance_check,
+            withdraw_balance_notify: self.withdraw_balance_notify,
+        }
+    }
+
+    pub fn withdraw_balance_check<WB2>(
+        self,
+        withdraw_balance_check: WB2,
+    ) -> Server<AR, CE, CC, HE, PE, PC, WE, WC, WB2, WN> {
+        Server {
+            auth_request: self.auth_request,
+            channel_entrypoint: self.channel_entrypoint,
+            channel_callback: self.channel_callback,
+            hosted_channel_entrypoint: self.hosted_channel_entrypoint,
+            pay_entrypoint: self.pay_entrypoint,
+            pay_callback: self.pay_callback,
+            withdraw_entrypoint: self.withdraw_entrypoint,
+            withdraw_callback: self.withdraw_callback,
+            withdraw_balance_check,
+            withdraw_balance_notify: self.withdraw_balance_notify,
+        }
+    }
+
+    /// Hook called with `k1` and the `balanceNotify` url whenever a withdraw callback carries one.
+    pub fn withdraw_balance_notify<WN2>(
+        self,
+        withdraw_balance_notify: WN2,
+    ) -> Server<AR, CE, CC, HE, PE, PC, WE, WC, WB, WN2> {
+        Server {
+            auth_request: self.auth_request,
+            channel_entrypoint: self.channel_entrypoint,
+            channel_callback: self.channel_callback,
+            hosted_channel_entrypoint: self.hosted_channel_entrypoint,
+            pay_entrypoint: self.pay_entrypoint,
+            pay_callback: self.pay_callback,
+            withdraw_entrypoint: self.withdraw_entrypoint,
+            withdraw_callback: self.withdraw_callback,
+            withdraw_balance_check: self.withdraw_balance_check,
+            withdraw_balance_notify,
         }
     }
 }
 
-impl<AR, ARFut, CE, CQFut, CC, CCFut, PE, PEFut, PC, PCFut, WE, WEFut, WC, WCFut>
-    Server<AR, CE, CC, PE, PC, WE, WC>
+impl<
+        AR,
+        ARFut,
+        CE,
+        CQFut,
+        CC,
+        CCFut,
+        HE,
+        HEFut,
+        PE,
+        PEFut,
+        PC,
+        PCFut,
+        WE,
+        WEFut,
+        WC,
+        WCFut,
+        WB,
+        WBFut,
+        WN,
+        WNFut,
+    > Server<AR, CE, CC, HE, PE, PC, WE, WC, WB, WN>
 where
     AR: 'static + Send + Clone + Fn(crate::auth::server::Callback) -> ARFut,
-    ARFut: Send + Future<Output = Result<crate::CallbackResponse, StatusCode>>,
+    ARFut: Send + Future<Output = Result<crate::CallbackResponse, Error>>,
 
     CE: 'static + Send + Clone + Fn(()) -> CQFut,
-    CQFut: Send + Future<Output = Result<crate::channel::server::Entrypoint, StatusCode>>,
+    CQFut: Send + Future<Output = Result<crate::channel::server::Entrypoint, Error>>,
 
     CC: 'static + Send + Clone + Fn(crate::channel::server::Callback) -> CCFut,
-    CCFut: Send + Future<Output = Result<crate::CallbackResponse, StatusCode>>,
+    CCFut: Send + Future<Output = Result<crate::CallbackResponse, Error>>,
+
+    HE: 'static + Send + Clone + Fn(()) -> HEFut,
+    HEFut: Send + Future<Output = Result<crate::hosted_channel::server::Entrypoint, Error>>,
 
     PE: 'static + Send + Clone + Fn(Option<String>) -> PEFut,
-    PEFut: Send + Future<Output = Result<crate::pay::server::Entrypoint, StatusCode>>,
+    PEFut: Send + Future<Output = Result<crate::pay::server::Entrypoint, Error>>,
 
     PC: 'static + Send + Clone + Fn(crate::pay::server::Callback) -> PCFut,
-    PCFut: Send + Future<Output = Result<crate::pay::server::CallbackResponse, StatusCode>>,
+    PCFut: Send + Future<Output = Result<crate::pay::server::CallbackResponse, Error>>,
 
     WE: 'static + Send + Clone + Fn(()) -> WEFut,
-    WEFut: Send + Future<Output = Result<crate::withdraw::server::Entrypoint, StatusCode>>,
+    WEFut: Send + Future<Output = Result<crate::withdraw::server::Entrypoint, Error>>,
 
     WC: 'static + Send + Clone + Fn(crate::withdraw::server::Callback) -> WCFut,
-    WCFut: Send + Future<Output = Result<crate::CallbackResponse, StatusCode>>,
+    WCFut: Send + Future<Output = Result<crate::CallbackResponse, Error>>,
+
+    WB: 'static + Send + Clone + Fn(String) -> WBFut,
+    WBFut: Send + Future<Output = Result<crate::withdraw::server::Entrypoint, Error>>,
+
+    WN: 'static + Send + Clone + Fn((String, url::Url)) -> WNFut,
+    WNFut: Send + Future<Output = ()>,
 {
     #[allow(clippy::too_many_lines)]
     pub fn build(self) -> Router<()> {
@@ -141,11 +249,16 @@ where
                 get(move |RawQuery(q): RawQuery| {
                     let ar = self.auth_request.clone();
                     async move {
-                        let q = q.ok_or(StatusCode::BAD_REQUEST)?;
-                        let p = q.as_str().try_into().map_err(|_| StatusCode::BAD_REQUEST)?;
-                        ar(p).await.and_then(|a| {
-                            Vec::<u8>::try_from(a).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
-                        })
+                        let q = q.ok_or_else(|| Error::bad_request("missing query"))?;
+                        let a = match q.as_str().try_into() {
+                            Ok(p) => ar(p).await?,
+                            Err(reason) => crate::CallbackResponse::Error {
+                                reason: reason.to_string(),
+                                extra: serde_json::Map::new(),
+                            },
+                        };
+                        Vec::<u8>::try_from(a)
+                            .map_err(|_| Error::Status(StatusCode::INTERNAL_SERVER_ERROR))
                     }
                 }),
             )
@@ -155,7 +268,8 @@ where
                     let ce = self.channel_entrypoint.clone();
                     async move {
                         ce(()).await.and_then(|a| {
-                            Vec::<u8>::try_from(a).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+                            Vec::<u8>::try_from(a)
+                                .map_err(|_| Error::Status(StatusCode::INTERNAL_SERVER_ERROR))
                         })
                     }
                 }),
@@ -165,10 +279,23 @@ where
                 get(move |RawQuery(q): RawQuery| {
                     let cc = self.channel_callback.clone();
                     async move {
-                        let q = q.ok_or(StatusCode::BAD_REQUEST)?;
-                        let p = q.as_str().try_into().map_err(|_| StatusCode::BAD_REQUEST)?;
+                        let q = q.ok_or_else(|| Error::bad_request("missing query"))?;
+                        let p = q.as_str().try_into().map_err(Error::bad_request)?;
                         cc(p).await.and_then(|a| {
-                            Vec::<u8>::try_from(a).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+                            Vec::<u8>::try_from(a)
+                                .map_err(|_| Error::Status(StatusCode::INTERNAL_SERVER_ERROR))
+                        })
+                    }
+                }),
+            )
+            .route(
+                "/lnurlh",
+                get(move || {
+                    let he = self.hosted_channel_entrypoint.clone();
+                    async move {
+                        he(()).await.and_then(|a| {
+                            Vec::<u8>::try_from(a)
+                                .map_err(|_| Error::Status(StatusCode::INTERNAL_SERVER_ERROR))
                         })
                     }
                 }),
@@ -182,7 +309,7 @@ where
                         async move {
                             pe(Some(identifier)).await.and_then(|a| {
                                 Vec::<u8>::try_from(a)
-                                    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+                                    .map_err(|_| Error::Status(StatusCode::INTERNAL_SERVER_ERROR))
                             })
                         }
                     }
@@ -194,7 +321,8 @@ where
                     let pe = self.pay_entrypoint.clone();
                     async move {
                         pe(None).await.and_then(|a| {
-                            Vec::<u8>::try_from(a).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+                            Vec::<u8>::try_from(a)
+                                .map_err(|_| Error::Status(StatusCode::INTERNAL_SERVER_ERROR))
                         })
                     }
                 }),
@@ -204,8 +332,8 @@ where
                 get(move |RawQuery(q): RawQuery| {
                     let pc = self.pay_callback.clone();
                     async move {
-                        let q = q.ok_or(StatusCode::BAD_REQUEST)?;
-                        let p = q.as_str().try_into().map_err(|_| StatusCode::BAD_REQUEST)?;
+                        let q = q.ok_or_else(|| Error::bad_request("missing query"))?;
+                        let p = q.as_str().try_into().map_err(Error::bad_request)?;
                         pc(p).await.map(|a| a.to_string())
                     }
                 }),
@@ -216,20 +344,42 @@ where
                     let we = self.withdraw_entrypoint.clone();
                     async move {
                         we(()).await.and_then(|a| {
-                            Vec::<u8>::try_from(a).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+                            Vec::<u8>::try_from(a)
+                                .map_err(|_| Error::Status(StatusCode::INTERNAL_SERVER_ERROR))
                         })
                     }
                 }),
             )
+            .route(
+                "/lnurlw/balance/:identifier",
+                get({
+                    let wb = self.withdraw_balance_check.clone();
+                    move |Path(identifier): Path<String>| {
+                        let wb = wb.clone();
+                        async move {
+                            wb(identifier).await.and_then(|a| {
+                                Vec::<u8>::try_from(a)
+                                    .map_err(|_| Error::Status(StatusCode::INTERNAL_SERVER_ERROR))
+                            })
+                        }
+                    }
+                }),
+            )
             .route(
                 "/lnurlw/callback",
                 get(move |RawQuery(q): RawQuery| {
                     let wc = self.withdraw_callback.clone();
+                    let wn = self.withdraw_balance_notify.clone();
                     async move {
-                        let q = q.ok_or(StatusCode::BAD_REQUEST)?;
-                        let p = q.as_str().try_into().map_err(|_| StatusCode::BAD_REQUEST)?;
+                        let q = q.ok_or_else(|| Error::bad_request("missing query"))?;
+                        let p: crate::withdraw::server::Callback =
+                            q.as_str().try_into().map_err(Error::bad_request)?;
+                        if let Some(url) = &p.balance_notify {
+                            wn((p.k1.clone(), url.clone())).await;
+                        }
                         wc(p).await.and_then(|a| {
-                            Vec::<u8>::try_from(a).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+                            Vec::<u8>::try_from(a)
+                                .map_err(|_| Error::Status(StatusCode::INTERNAL_SERVER_ERROR))
                         })
                     }
                 }),
@@ -237,7 +387,66 @@ where
     }
 }
 
+/// Failures handlers can reply with.
+#[derive(Clone, Debug)]
+pub enum Error {
+    /// Bare HTTP status with an empty body.
+    Status(StatusCode),
+    /// LNURL `{"status":"ERROR","reason":...}` body, sent with `status`.
+    Reason { status: StatusCode, reason: String },
+}
+
+impl Error {
+    /// LNURL error with `reason`, sent with `200 OK` as most wallets expect.
+    pub fn reason(reason: impl Into<String>) -> Self {
+        Error::Reason {
+            status: StatusCode::OK,
+            reason: reason.into(),
+        }
+    }
+
+    /// Same error, sent with `status` instead.
+    #[must_use]
+    pub fn with_status(self, status: StatusCode) -> Self {
+        match self {
+            Error::Status(_) => Error::Status(status),
+            Error::Reason { reason, .. } => Error::Reason { status, reason },
+        }
+    }
+
+    fn bad_request(reason: impl std::fmt::Display) -> Self {
+        Error::Reason {
+            status: StatusCode::BAD_REQUEST,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl From<StatusCode> for Error {
+    fn from(status: StatusCode) -> Self {
+        Error::Status(status)
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        match self {
+            Error::Status(status) => status.into_response(),
+            Error::Reason { status, reason } => {
+                match Vec::<u8>::try_from(crate::CallbackResponse::Error {
+                    reason,
+                    extra: serde_json::Map::new(),
+                }) {
+                    Ok(body) => (status, body).into_response(),
+                    Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+                }
+            }
+        }
+    }
+}
+
 mod unimplemented {
+    use super::Error;
     use axum::http::StatusCode;
     use std::{
         future::Future,
@@ -251,13 +460,18 @@ mod unimplemented {
         Unimplemented(PhantomData)
     }
 
+    pub(super) type Ignore<Param> = fn(Param) -> std::future::Ready<()>;
+    pub(super) fn ignore<Param>(_: Param) -> std::future::Ready<()> {
+        std::future::ready(())
+    }
+
     pub struct Unimplemented<T>(PhantomData<T>);
 
     impl<T> Future for Unimplemented<T> {
-        type Output = Result<T, StatusCode>;
+        type Output = Result<T, Error>;
 
-        fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<T, StatusCode>> {
-            Poll::Ready(Err(StatusCode::NOT_IMPLEMENTED))
+        fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<T, Error>> {
+            Poll::Ready(Err(Error::Status(StatusCode::NOT_IMPLEMENTED)))
         }
     }
 }
@@ -0,0 +1,203 @@
+const ADDR: &str = "lnurlkit.onion";
+
+/// Spawns the stand-in proxy, returning its url.
+async fn proxy() -> String {
+    use axum::{
+        http::{HeaderMap, Uri},
+        response::Redirect,
+        routing::get,
+    };
+
+    // Onion services may be reached over plain http, through a proxy. The local server stands in
+    // for that proxy, answering the absolute-form requests sent to it on the service's behalf.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("net");
+
+    let proxy = format!("http://{}", listener.local_addr().expect("addr"));
+
+    let router = axum::Router::new()
+        .route(
+            "/lnurlw",
+            get(move |uri: Uri, headers: HeaderMap| async move {
+                let proxied = uri.host() == Some(ADDR);
+                let authorized = matches!(headers.get("x-api-key"), Some(v) if v == "chave");
+                let agent = matches!(headers.get("user-agent"), Some(v) if v == "carteira");
+
+                if !proxied {
+                    return Err(axum::http::StatusCode::MISDIRECTED_REQUEST);
+                }
+
+                if !authorized || !agent {
+                    return Err(axum::http::StatusCode::FORBIDDEN);
+                }
+
+                Ok(format!(
+                    r#"{{
+                        "tag": "withdrawRequest",
+                        "k1": "caum",
+                        "callback": "http://{ADDR}/lnurlw/callback",
+                        "minWithdrawable": 314,
+                        "maxWithdrawable": 315,
+                        "defaultDescription": "descricao"
+                    }}"#
+                ))
+            }),
+        )
+        .route("/moved", get(|| async { Redirect::temporary("/lnurlw") }))
+        .route(
+            "/escape",
+            get(|| async { Redirect::temporary("http://lnurlkit.test/lnurlw") }),
+        )
+        .route("/big", get(|| async { vec![b' '; 1 << 20] }))
+        .route("/huge", get(|| async { vec![b' '; (1 << 20) + 1] }));
+
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.expect("serve");
+    });
+
+    proxy
+}
+
+fn lnurl(path: &str) -> String {
+    let url = url::Url::parse(&format!("http://{ADDR}{path}")).expect("url");
+    lnurlkit::lnurl::encode(&url).expect("lnurl")
+}
+
+#[tokio::test]
+async fn test() {
+    let proxy = proxy().await;
+
+    let client = lnurlkit::Client::builder()
+        .onion_proxy(&proxy)
+        .timeout(std::time::Duration::from_secs(5))
+        .max_body_size(4096)
+        .user_agent("carteira")
+        .header("x-api-key", "chave")
+        .build()
+        .expect("client");
+
+    let queried = client.entrypoint(&lnurl("/moved")).await.expect("query");
+    let lnurlkit::client::Entrypoint::Withdraw(wr) = queried else {
+        panic!("not withdraw request");
+    };
+
+    assert_eq!(wr.core.min, 314);
+
+    let Err(lnurlkit::client::Error::Transport(err)) = client.entrypoint(&lnurl("/big")).await
+    else {
+        panic!("expected transport error");
+    };
+
+    let source = std::error::Error::source(&err).expect("source");
+    assert!(source.is::<lnurlkit::client::transport::BodyTooLarge>());
+
+    let client = lnurlkit::Client::builder()
+        .onion_proxy(&proxy)
+        .max_redirects(0)
+        .user_agent("carteira")
+        .header("x-api-key", "chave")
+        .build()
+        .expect("client");
+
+    let result = client.entrypoint(&lnurl("/moved")).await;
+    assert!(matches!(result, Err(lnurlkit::client::Error::Status(307))));
+
+    let client = lnurlkit::Client::builder()
+        .onion_proxy(&proxy)
+        .build()
+        .expect("client");
+
+    let result = client.entrypoint(&lnurl("/lnurlw")).await;
+    assert!(matches!(result, Err(lnurlkit::client::Error::Status(403))));
+
+    let result = lnurlkit::Client::builder()
+        .header("x-api-key", "chave\n")
+        .build();
+    assert!(result.is_err());
+
+    let client = lnurlkit::Client::builder()
+        .proxy(&proxy)
+        .user_agent("carteira")
+        .header("x-api-key", "chave")
+        .build()
+        .expect("client");
+
+    let queried = client.entrypoint(&lnurl("/lnurlw")).await.expect("query");
+    assert!(matches!(queried, lnurlkit::client::Entrypoint::Withdraw(_)));
+
+    let result = lnurlkit::Client::builder().proxy("no proxy").build();
+    assert!(result.is_err());
+
+    let result = lnurlkit::Client::builder().onion_proxy("no proxy").build();
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn default_body_limit() {
+    let client = lnurlkit::Client::builder()
+        .onion_proxy(proxy().await)
+        .build()
+        .expect("client");
+
+    let Err(lnurlkit::client::Error::Transport(err)) = client.entrypoint(&lnurl("/huge")).await
+    else {
+        panic!("expected transport error");
+    };
+
+    let source = std::error::Error::source(&err).expect("source");
+    assert!(source.is::<lnurlkit::client::transport::BodyTooLarge>());
+}
+
+#[tokio::test]
+async fn insecure_redirect() {
+    let client = lnurlkit::Client::builder()
+        .proxy(proxy().await)
+        .build()
+        .expect("client");
+
+    let Err(lnurlkit::client::Error::Transport(err)) = client.entrypoint(&lnurl("/escape")).await
+    else {
+        panic!("expected transport error");
+    };
+
+    let insecure =
+        std::iter::successors(std::error::Error::source(&err), |e| e.source()).any(|e| {
+            matches!(
+                e.downcast_ref(),
+                Some(lnurlkit::error::ResolveError::Insecure)
+            )
+        });
+
+    assert!(insecure);
+}
+
+#[tokio::test]
+async fn in_memory_body_limit() {
+    use axum::routing::get;
+
+    let limit = lnurlkit::client::transport::DEFAULT_MAX_BODY_SIZE;
+    let router = axum::Router::new()
+        .route("/fits", get(move || async move { vec![b' '; limit] }))
+        .route("/huge", get(move || async move { vec![b' '; limit + 1] }));
+
+    let transport = lnurlkit::client::transport::InMemory::new(router);
+
+    let response = lnurlkit::client::Transport::get(&transport, "https://lnurlkit.test/fits")
+        .await
+        .expect("fits");
+    assert_eq!(response.body.len(), limit);
+
+    let err = lnurlkit::client::Transport::get(&transport, "https://lnurlkit.test/huge")
+        .await
+        .expect_err("huge");
+
+    let source = std::error::Error::source(&err).expect("source");
+    assert!(source.is::<lnurlkit::client::transport::BodyTooLarge>());
+
+    let transport = transport.max_body_size(limit + 1);
+    let response = lnurlkit::client::Transport::get(&transport, "https://lnurlkit.test/huge")
+        .await
+        .expect("huge");
+    assert_eq!(response.body.len(), limit + 1);
+}
@@ -1,13 +1,9 @@
 #[tokio::test]
 async fn test() {
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:0")
-        .await
-        .expect("net");
+    let addr = "lnurlkit.test";
 
-    let addr = listener.local_addr().expect("addr");
-
-    let query_url = format!("http://{addr}/lnurlc");
-    let callback_url = url::Url::parse(&format!("http://{addr}/lnurlc/callback")).expect("url");
+    let query_url = format!("https://{addr}/lnurlc");
+    let callback_url = url::Url::parse(&format!("https://{addr}/lnurlc/callback")).expect("url");
 
     let router = lnurlkit::Server::default()
         .channel_request(
@@ -25,10 +21,15 @@ async fn test() {
                 Ok(match req {
                     lnurlkit::channel::server::Callback::Cancel { remoteid, k1 } => {
                         if &remoteid as &str == "idremoto" {
-                            lnurlkit::CallbackResponse::Ok
+                            lnurlkit::CallbackResponse::Ok {
+                                extra: serde_json::Map::new(),
+                            }
                         } else {
                             let reason = format!("Cancel/{k1}/{remoteid}");
-                            lnurlkit::CallbackResponse::Error { reason }
+                            lnurlkit::CallbackResponse::Error {
+                                reason,
+                                extra: serde_json::Map::new(),
+                            }
                         }
                     }
                     lnurlkit::channel::server::Callback::Accept {
@@ -37,25 +38,19 @@ async fn test() {
                         k1,
                     } => {
                         let reason = format!("Accept/{k1}/{remoteid}/{private}");
-                        lnurlkit::CallbackResponse::Error { reason }
+                        lnurlkit::CallbackResponse::Error {
+                            reason,
+                            extra: serde_json::Map::new(),
+                        }
                     }
                 })
             },
         )
         .build();
 
-    tokio::spawn(async move {
-        axum::serve(listener, router).await.expect("serve");
-    });
-
-    let client = lnurlkit::Client::default();
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
 
-    let lnurl = bech32::encode(
-        "lnurl",
-        bech32::ToBase32::to_base32(&query_url),
-        bech32::Variant::Bech32,
-    )
-    .expect("lnurl");
+    let lnurl = lnurlkit::lnurl::encode(&url::Url::parse(&query_url).expect("url")).expect("lnurl");
 
     let queried = client.entrypoint(&lnurl).await.expect("query");
     let lnurlkit::client::Entrypoint::Channel(cr) = queried else {
@@ -66,26 +61,26 @@ async fn test() {
 
     let response = cr.cancel("idremoto").await.expect("callback");
 
-    assert!(matches!(response, lnurlkit::CallbackResponse::Ok));
+    assert!(matches!(response, lnurlkit::CallbackResponse::Ok { .. }));
 
     let response = cr.cancel("iderrado").await.expect("callback");
 
     assert!(matches!(
         response,
-        lnurlkit::CallbackResponse::Error { reason } if &reason as &str == "Cancel/caum/iderrado"
+        lnurlkit::CallbackResponse::Error { reason, .. } if &reason as &str == "Cancel/caum/iderrado"
     ));
 
     let response = cr.accept("iderrado", true).await.expect("callback");
 
     assert!(matches!(
         response,
-        lnurlkit::CallbackResponse::Error { reason } if &reason as &str == "Accept/caum/iderrado/true"
+        lnurlkit::CallbackResponse::Error { reason, .. } if &reason as &str == "Accept/caum/iderrado/true"
     ));
 
     let response = cr.accept("iderrado", false).await.expect("callback");
 
     assert!(matches!(
         response,
-        lnurlkit::CallbackResponse::Error { reason } if &reason as &str == "Accept/caum/iderrado/false"
+        lnurlkit::CallbackResponse::Error { reason, .. } if &reason as &str == "Accept/caum/iderrado/false"
     ));
 }
@@ -1,13 +1,9 @@
 #[tokio::test]
 async fn test() {
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:0")
-        .await
-        .expect("net");
-
-    let addr = listener.local_addr().expect("addr");
+    let addr = "lnurlkit.test";
 
-    let query_url = format!("http://{addr}/lnurlw");
-    let callback_url = url::Url::parse(&format!("http://{addr}/lnurlw/callback")).expect("url");
+    let query_url = format!("https://{addr}/lnurlw");
+    let callback_url = url::Url::parse(&format!("https://{addr}/lnurlw/callback")).expect("url");
 
     let router = lnurlkit::Server::default()
         .withdraw_request(
@@ -20,33 +16,29 @@ async fn test() {
                         callback,
                         min: 314,
                         max: 315,
+                        balance_check: None,
+                        pay_link: None,
                     })
                 }
             },
             |req: lnurlkit::withdraw::server::Callback| async move {
                 Ok(if &req.pr as &str == "pierre" {
-                    lnurlkit::CallbackResponse::Ok
+                    let mut extra = serde_json::Map::new();
+                    extra.insert(String::from("txid"), serde_json::Value::from("caum"));
+                    lnurlkit::CallbackResponse::Ok { extra }
                 } else {
                     lnurlkit::CallbackResponse::Error {
-                        reason: req.k1.to_string(),
+                        reason: req.k1,
+                        extra: serde_json::Map::new(),
                     }
                 })
             },
         )
         .build();
 
-    tokio::spawn(async move {
-        axum::serve(listener, router).await.expect("serve");
-    });
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
 
-    let client = lnurlkit::Client::default();
-
-    let lnurl = bech32::encode(
-        "lnurl",
-        bech32::ToBase32::to_base32(&query_url),
-        bech32::Variant::Bech32,
-    )
-    .expect("lnurl");
+    let lnurl = lnurlkit::lnurl::encode(&url::Url::parse(&query_url).expect("url")).expect("lnurl");
 
     let queried = client.entrypoint(&lnurl).await.expect("query");
     let lnurlkit::client::Entrypoint::Withdraw(wr) = queried else {
@@ -57,14 +49,95 @@ async fn test() {
     assert_eq!(wr.core.max, 315);
     assert_eq!(&wr.core.description as &str, "descricao");
 
-    let response = wr.submit("pierre").await.expect("callback");
+    let response = wr.submit("pierre", None).await.expect("callback");
 
-    assert!(matches!(response, lnurlkit::CallbackResponse::Ok));
+    assert!(matches!(
+        response,
+        lnurlkit::CallbackResponse::Ok { extra } if extra["txid"] == "caum"
+    ));
 
-    let response = wr.submit("pierrado").await.expect("callback");
+    let response = wr.submit("pierrado", None).await.expect("callback");
 
     assert!(matches!(
         response,
-        lnurlkit::CallbackResponse::Error { reason } if &reason as &str == "caum"
+        lnurlkit::CallbackResponse::Error { reason, .. } if &reason as &str == "caum"
     ));
 }
+
+#[tokio::test]
+async fn error() {
+    let addr = "lnurlkit.test";
+
+    let router = lnurlkit::Server::default()
+        .withdraw_request(
+            |()| async {
+                Err(lnurlkit::server::Error::reason("link expired")
+                    .with_status(axum::http::StatusCode::GONE))
+            },
+            |_: lnurlkit::withdraw::server::Callback| async {
+                Err(lnurlkit::server::Error::reason("already paid"))
+            },
+        )
+        .build();
+
+    let transport = lnurlkit::client::transport::InMemory::new(router);
+
+    let response = lnurlkit::client::Transport::get(&transport, &format!("https://{addr}/lnurlw"))
+        .await
+        .expect("request");
+
+    assert_eq!(response.status, 410);
+    assert_eq!(
+        response.body,
+        br#"{"status":"ERROR","reason":"link expired"}"#
+    );
+
+    let response = lnurlkit::client::Transport::get(
+        &transport,
+        &format!("https://{addr}/lnurlw/callback?k1=caum&pr=pierre"),
+    )
+    .await
+    .expect("request");
+
+    assert_eq!(response.status, 200);
+    assert_eq!(
+        response.body,
+        br#"{"status":"ERROR","reason":"already paid"}"#
+    );
+
+    let response =
+        lnurlkit::client::Transport::get(&transport, &format!("https://{addr}/lnurlw/callback"))
+            .await
+            .expect("request");
+
+    assert_eq!(response.status, 400);
+    assert_eq!(
+        response.body,
+        br#"{"status":"ERROR","reason":"missing query"}"#
+    );
+
+    let client = lnurlkit::Client::new(transport);
+
+    let url = url::Url::parse(&format!("https://{addr}/lnurlw")).expect("url");
+    let lnurl = lnurlkit::lnurl::encode(&url).expect("lnurl");
+
+    let Err(err) = client.entrypoint(&lnurl).await else {
+        panic!("expected error");
+    };
+
+    assert!(matches!(
+        &err,
+        lnurlkit::client::Error::Protocol { reason, .. } if reason == "link expired"
+    ));
+    assert!(!err.is_transient());
+
+    let url = url::Url::parse(&format!("https://{addr}/lnurlc")).expect("url");
+    let lnurl = lnurlkit::lnurl::encode(&url).expect("lnurl");
+
+    let Err(err) = client.entrypoint(&lnurl).await else {
+        panic!("expected error");
+    };
+
+    assert!(matches!(err, lnurlkit::client::Error::Status(501)));
+    assert!(!err.is_transient());
+}
@@ -1,37 +1,35 @@
 #[tokio::test]
 async fn test() {
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:0")
-        .await
-        .expect("net");
+    let addr = "lnurlkit.test";
+
+    let url = format!("https://{addr}/keyauth?tag=login&k1=3031323334353637383930313233343536373839303132333435363738393031");
 
-    let addr = listener.local_addr().expect("addr");
+    let master =
+        lnurlkit::auth::linking::Bip32Master::from_seed(b"sementesecreta").expect("master");
+    let key = master
+        .linking_key(&url::Url::parse(&url).expect("url"))
+        .expect("key");
 
-    let url = format!("http://{addr}/keyauth?tag=login&k1=3031323334353637383930313233343536373839303132333435363738393031");
+    let pubkey = key.public_key();
 
     let router = lnurlkit::Server::default()
         .auth(move |req: lnurlkit::auth::server::Callback| async move {
-            if &req.sig == b"0123456789012345678901234567890101234567890123456789012345678901" {
-                Ok(lnurlkit::CallbackResponse::Ok)
+            if req.key == pubkey && &req.k1 == b"01234567890123456789012345678901" {
+                Ok(lnurlkit::CallbackResponse::Ok {
+                    extra: serde_json::Map::new(),
+                })
             } else {
                 Ok(lnurlkit::CallbackResponse::Error {
-                    reason: String::from("bad sig"),
+                    reason: String::from("unknown key"),
+                    extra: serde_json::Map::new(),
                 })
             }
         })
         .build();
 
-    tokio::spawn(async move {
-        axum::serve(listener, router).await.expect("serve");
-    });
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
 
-    let client = lnurlkit::Client::default();
-
-    let lnurl = bech32::encode(
-        "lnurl",
-        bech32::ToBase32::to_base32(&url),
-        bech32::Variant::Bech32,
-    )
-    .expect("lnurl");
+    let lnurl = lnurlkit::lnurl::encode(&url::Url::parse(&url).expect("url")).expect("lnurl");
 
     let queried = client.entrypoint(&lnurl).await.expect("query");
     let lnurlkit::client::Entrypoint::Auth(a) = queried else {
@@ -42,13 +40,20 @@ async fn test() {
     assert_eq!(&a.core.k1, b"01234567890123456789012345678901");
     assert!(a.core.action.is_none());
 
+    let response = a.auth_with(&key).await.expect("callback");
+
+    assert!(matches!(response, lnurlkit::CallbackResponse::Ok { .. }));
+
     let response = a
         .auth(
-            "pierre",
-            b"0123456789012345678901234567890101234567890123456789012345678901",
+            &key.public_key(),
+            &key.sign(b"10987654321098765432109876543210"),
         )
         .await
         .expect("callback");
 
-    assert!(matches!(response, lnurlkit::CallbackResponse::Ok));
+    assert!(matches!(
+        response,
+        lnurlkit::CallbackResponse::Error { reason, .. } if &reason as &str == "bad signature"
+    ));
 }
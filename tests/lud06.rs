@@ -1,13 +1,12 @@
+use lnurlkit::{client::Error, error::InvoiceError};
+
 #[tokio::test]
+#[allow(clippy::too_many_lines)]
 async fn test() {
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:0")
-        .await
-        .expect("net");
-
-    let addr = listener.local_addr().expect("addr");
+    let addr = "lnurlkit.test";
 
-    let query_url = format!("http://{addr}/lnurlp");
-    let callback_url = url::Url::parse(&format!("http://{addr}/lnurlp/callback")).expect("url");
+    let query_url = format!("https://{addr}/lnurlp");
+    let callback_url = url::Url::parse(&format!("https://{addr}/lnurlp/callback")).expect("url");
 
     let router = lnurlkit::Server::default()
         .pay_request(
@@ -27,31 +26,27 @@ async fn test() {
                         email: None,
                         currencies: None,
                         payer: None,
+                        extra_metadata: lnurlkit::pay::Metadata::default(),
                     })
                 }
             },
             |req: lnurlkit::pay::server::Callback| async move {
-                Ok(lnurlkit::pay::server::CallbackResponse {
-                    pr: format!("pierre:{:?}", req.amount),
+                Ok(lnurlkit::pay::server::CallbackResponse::Ok {
+                    pr: match req.comment.as_deref() {
+                        Some("bolt11") => String::from(INVOICE),
+                        _ => format!("pierre:{:?}", req.amount),
+                    },
                     disposable: false,
                     success_action: None,
+                    extra: serde_json::Map::new(),
                 })
             },
         )
         .build();
 
-    tokio::spawn(async move {
-        axum::serve(listener, router).await.expect("serve");
-    });
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
 
-    let client = lnurlkit::Client::default();
-
-    let lnurl = bech32::encode(
-        "lnurl",
-        bech32::ToBase32::to_base32(&query_url),
-        bech32::Variant::Bech32,
-    )
-    .expect("lnurl");
+    let lnurl = lnurlkit::lnurl::encode(&url::Url::parse(&query_url).expect("url")).expect("lnurl");
 
     let queried = client.entrypoint(&lnurl).await.expect("query");
     let lnurlkit::client::Entrypoint::Pay(pr) = queried else {
@@ -62,11 +57,11 @@ async fn test() {
     assert_eq!(pr.core.max, 315);
     assert_eq!(pr.core.short_description, "today i become death");
     assert_eq!(
-        &pr.core.long_description.as_ref().unwrap() as &str,
+        pr.core.long_description.as_ref().unwrap() as &str,
         "the destroyer of worlds"
     );
 
-    let invoice = pr
+    let lnurlkit::client::Invoice { pr: invoice, .. } = pr
         .invoice(
             &lnurlkit::pay::Amount::Millisatoshis(314),
             Some("comment"),
@@ -76,5 +71,147 @@ async fn test() {
         .await
         .expect("callback");
 
-    assert_eq!(&invoice.pr as &str, "pierre:Millisatoshis(314)");
+    assert_eq!(&invoice as &str, "pierre:Millisatoshis(314)");
+
+    let verified = pr
+        .verified_invoice(
+            &lnurlkit::pay::Amount::Millisatoshis(314),
+            Some("bolt11"),
+            None,
+            None,
+            lnurlkit::bolt11::Network::Bitcoin,
+        )
+        .await
+        .expect("verified");
+
+    assert_eq!(verified.pr, INVOICE);
+    assert!(!verified.disposable);
+    assert!(verified.success_action.is_none());
+    assert_eq!(verified.invoice.amount, Some(314));
+    assert_eq!(verified.invoice.payment_hash, [7; 32]);
+
+    let result = pr
+        .verified_invoice(
+            &lnurlkit::pay::Amount::Millisatoshis(315),
+            Some("bolt11"),
+            None,
+            None,
+            lnurlkit::bolt11::Network::Bitcoin,
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(Error::Invoice(InvoiceError::WrongAmount))
+    ));
+
+    let result = pr
+        .verified_invoice(
+            &lnurlkit::pay::Amount::Millisatoshis(314),
+            Some("bolt11"),
+            None,
+            None,
+            lnurlkit::bolt11::Network::Regtest,
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(Error::Invoice(InvoiceError::WrongNetwork))
+    ));
+}
+
+// 314 msats, description hash of the metadata above and expiry far in the future.
+const INVOICE: &str = "lnbc3140p1pj48ugqpp5qurswpc8qurswpc8qurswpc8qurswpc8qurswpc8qurswpc8qurshp57cnau7cdfc9ta2clx6tg4fccjxf27v7ksc46mg7ecpzjy2hu8wzsxq8rhxk2qqegjv68n6z7cez0sgre35p65y9jw662hgv7ctvv6jys7zc03l977hr2w8c03ku9pklrrhd9apr7d0npqjhvtmdh3kuhej5apd8d5gt8cqpez20r";
+
+#[tokio::test]
+async fn error() {
+    let addr = "lnurlkit.test";
+
+    let query_url = format!("https://{addr}/lnurlp");
+    let callback_url = url::Url::parse(&format!("https://{addr}/lnurlp/callback")).expect("url");
+
+    let router = lnurlkit::Server::default()
+        .pay_request(
+            move |_| {
+                let callback = callback_url.clone();
+                async {
+                    Ok(lnurlkit::pay::server::Entrypoint {
+                        callback,
+                        short_description: String::from("today i become death"),
+                        long_description: None,
+                        jpeg: None,
+                        png: None,
+                        comment_size: None,
+                        min: 314,
+                        max: 315,
+                        identifier: None,
+                        email: None,
+                        currencies: None,
+                        payer: None,
+                        extra_metadata: lnurlkit::pay::Metadata::default(),
+                    })
+                }
+            },
+            |req: lnurlkit::pay::server::Callback| async move {
+                match req.amount {
+                    lnurlkit::pay::Amount::Millisatoshis(314) => {
+                        Ok(lnurlkit::pay::server::CallbackResponse::Error {
+                            reason: String::from("amount too low"),
+                            extra: serde_json::Map::new(),
+                        })
+                    }
+                    _ => Err(lnurlkit::server::Error::reason("amount too high")),
+                }
+            },
+        )
+        .build();
+
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
+
+    let lnurl = lnurlkit::lnurl::encode(&url::Url::parse(&query_url).expect("url")).expect("lnurl");
+
+    let queried = client.entrypoint(&lnurl).await.expect("query");
+    let lnurlkit::client::Entrypoint::Pay(pr) = queried else {
+        panic!("not pay request");
+    };
+
+    let result = pr
+        .invoice(&lnurlkit::pay::Amount::Millisatoshis(314), None, None, None)
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(Error::Protocol { reason, .. }) if reason == "amount too low"
+    ));
+
+    let result = pr
+        .verified_invoice(
+            &lnurlkit::pay::Amount::Millisatoshis(314),
+            None,
+            None,
+            None,
+            lnurlkit::bolt11::Network::Bitcoin,
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(Error::Protocol { reason, .. }) if reason == "amount too low"
+    ));
+
+    let result = pr
+        .verified_invoice(
+            &lnurlkit::pay::Amount::Millisatoshis(315),
+            None,
+            None,
+            None,
+            lnurlkit::bolt11::Network::Bitcoin,
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(Error::Protocol { reason, .. }) if reason == "amount too high"
+    ));
 }
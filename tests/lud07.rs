@@ -0,0 +1,29 @@
+#[tokio::test]
+async fn test() {
+    let addr = "lnurlkit.test";
+
+    let query_url = format!("https://{addr}/lnurlh");
+
+    let router = lnurlkit::Server::default()
+        .hosted_channel_request(|()| async {
+            Ok(lnurlkit::hosted_channel::server::Entrypoint {
+                uri: String::from("u@r:i"),
+                k1: String::from("caum"),
+                alias: Some(String::from("apelido")),
+            })
+        })
+        .build();
+
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
+
+    let lnurl = lnurlkit::lnurl::encode(&url::Url::parse(&query_url).expect("url")).expect("lnurl");
+
+    let queried = client.entrypoint(&lnurl).await.expect("query");
+    let lnurlkit::client::Entrypoint::HostedChannel(hc) = queried else {
+        panic!("not hosted channel request");
+    };
+
+    assert_eq!(&hc.core.uri as &str, "u@r:i");
+    assert_eq!(&hc.core.k1 as &str, "caum");
+    assert_eq!(hc.core.alias.as_deref(), Some("apelido"));
+}
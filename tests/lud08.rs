@@ -1,13 +1,9 @@
 #[tokio::test]
 async fn test() {
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:0")
-        .await
-        .expect("net");
+    let addr = "lnurlkit.test";
 
-    let addr = listener.local_addr().expect("addr");
-
-    let callback = url::Url::parse(&format!("http://{addr}/lnurlw/callback")).expect("url");
-    let callback2 = url::Url::parse(&format!("http://{addr}/lnurlw/callback")).expect("url");
+    let callback = url::Url::parse(&format!("https://{addr}/lnurlw/callback")).expect("url");
+    let callback2 = url::Url::parse(&format!("https://{addr}/lnurlw/callback")).expect("url");
 
     let w = lnurlkit::withdraw::server::Entrypoint {
         description: String::from("descricao"),
@@ -15,9 +11,11 @@ async fn test() {
         callback: callback.clone(),
         min: 314,
         max: 315,
+        balance_check: None,
+        pay_link: None,
     };
 
-    let query_url_slow = format!("http://{addr}/lnurlw");
+    let query_url_slow = format!("https://{addr}/lnurlw");
     let query_url_fast = format!("{query_url_slow}?{w}");
 
     let router = lnurlkit::Server::default()
@@ -31,6 +29,8 @@ async fn test() {
                         callback,
                         min: 123,
                         max: 321,
+                        balance_check: None,
+                        pay_link: None,
                     })
                 }
             },
@@ -38,18 +38,10 @@ async fn test() {
         )
         .build();
 
-    tokio::spawn(async move {
-        axum::serve(listener, router).await.expect("serve");
-    });
-
-    let client = lnurlkit::Client::default();
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
 
-    let lnurl = bech32::encode(
-        "lnurl",
-        bech32::ToBase32::to_base32(&query_url_slow),
-        bech32::Variant::Bech32,
-    )
-    .expect("lnurl");
+    let lnurl =
+        lnurlkit::lnurl::encode(&url::Url::parse(&query_url_slow).expect("url")).expect("lnurl");
 
     let queried = client.entrypoint(&lnurl).await.expect("query");
     let lnurlkit::client::Entrypoint::Withdraw(wr) = queried else {
@@ -61,12 +53,8 @@ async fn test() {
     assert_eq!(&wr.core.description as &str, "outra-descricao");
     assert_eq!(wr.core.callback, callback2);
 
-    let lnurl = bech32::encode(
-        "lnurl",
-        bech32::ToBase32::to_base32(&query_url_fast),
-        bech32::Variant::Bech32,
-    )
-    .expect("lnurl");
+    let lnurl =
+        lnurlkit::lnurl::encode(&url::Url::parse(&query_url_fast).expect("url")).expect("lnurl");
 
     let queried = client.entrypoint(&lnurl).await.expect("query");
     let lnurlkit::client::Entrypoint::Withdraw(wr) = queried else {
@@ -1,13 +1,9 @@
 #[tokio::test]
 async fn test() {
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:0")
-        .await
-        .expect("net");
-
-    let addr = listener.local_addr().expect("addr");
+    let addr = "lnurlkit.test";
 
-    let query_url = format!("http://{addr}/lnurlp");
-    let callback_url = url::Url::parse(&format!("http://{addr}/lnurlp/callback")).expect("url");
+    let query_url = format!("https://{addr}/lnurlp");
+    let callback_url = url::Url::parse(&format!("https://{addr}/lnurlp/callback")).expect("url");
 
     let router = lnurlkit::Server::default()
         .pay_request(
@@ -27,11 +23,12 @@ async fn test() {
                         email: None,
                         currencies: None,
                         payer: None,
+                        extra_metadata: lnurlkit::pay::Metadata::default(),
                     })
                 }
             },
             |req: lnurlkit::pay::server::Callback| async move {
-                Ok(lnurlkit::pay::server::CallbackResponse {
+                Ok(lnurlkit::pay::server::CallbackResponse::Ok {
                     pr: String::new(),
                     disposable: false,
                     success_action: if matches!(req.amount, lnurlkit::pay::Amount::Millisatoshis(0))
@@ -39,45 +36,37 @@ async fn test() {
                         None
                     } else if matches!(req.amount, lnurlkit::pay::Amount::Millisatoshis(1)) {
                         Some(lnurlkit::pay::server::SuccessAction::Message(
-                            req.comment.map(|a| a.to_string()).unwrap_or_default(),
+                            req.comment.unwrap_or_default(),
                         ))
                     } else {
                         Some(lnurlkit::pay::server::SuccessAction::Url(
                             url::Url::parse("http://u.rl").expect("url"),
-                            req.comment.map(|a| a.to_string()).unwrap_or_default(),
+                            req.comment.unwrap_or_default(),
                         ))
                     },
+                    extra: serde_json::Map::new(),
                 })
             },
         )
         .build();
 
-    tokio::spawn(async move {
-        axum::serve(listener, router).await.expect("serve");
-    });
-
-    let client = lnurlkit::Client::default();
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
 
-    let lnurl = bech32::encode(
-        "lnurl",
-        bech32::ToBase32::to_base32(&query_url),
-        bech32::Variant::Bech32,
-    )
-    .expect("lnurl");
+    let lnurl = lnurlkit::lnurl::encode(&url::Url::parse(&query_url).expect("url")).expect("lnurl");
 
     let queried = client.entrypoint(&lnurl).await.expect("query");
     let lnurlkit::client::Entrypoint::Pay(pr) = queried else {
         panic!("not pay request");
     };
 
-    let invoice = pr
+    let lnurlkit::client::Invoice { success_action, .. } = pr
         .invoice(&lnurlkit::pay::Amount::Millisatoshis(0), None, None, None)
         .await
         .expect("callback");
 
-    assert!(invoice.success_action.is_none());
+    assert!(success_action.is_none());
 
-    let invoice = pr
+    let lnurlkit::client::Invoice { success_action, .. } = pr
         .invoice(
             &lnurlkit::pay::Amount::Millisatoshis(1),
             Some("mensagem"),
@@ -87,13 +76,13 @@ async fn test() {
         .await
         .expect("callback");
 
-    let Some(lnurlkit::pay::client::SuccessAction::Message(m)) = invoice.success_action else {
+    let Some(lnurlkit::pay::client::SuccessAction::Message(m)) = success_action else {
         panic!("bad success action");
     };
 
     assert_eq!(&m as &str, "mensagem");
 
-    let invoice = pr
+    let lnurlkit::client::Invoice { success_action, .. } = pr
         .invoice(
             &lnurlkit::pay::Amount::Millisatoshis(2),
             Some("descricao"),
@@ -103,7 +92,7 @@ async fn test() {
         .await
         .expect("callback");
 
-    let Some(lnurlkit::pay::client::SuccessAction::Url(u, d)) = invoice.success_action else {
+    let Some(lnurlkit::pay::client::SuccessAction::Url(u, d)) = success_action else {
         panic!("bad success action");
     };
 
@@ -0,0 +1,70 @@
+#[tokio::test]
+async fn test() {
+    let addr = "lnurlkit.test";
+
+    let callback_url = url::Url::parse(&format!("https://{addr}/lnurlp/callback")).expect("url");
+
+    let router = lnurlkit::Server::default()
+        .pay_request(
+            move |_| {
+                let callback = callback_url.clone();
+                async {
+                    Ok(lnurlkit::pay::server::Entrypoint {
+                        callback,
+                        short_description: String::new(),
+                        long_description: None,
+                        jpeg: None,
+                        png: None,
+                        comment_size: None,
+                        min: 314,
+                        max: 315,
+                        identifier: None,
+                        email: None,
+                        currencies: None,
+                        payer: None,
+                        extra_metadata: lnurlkit::pay::Metadata::default(),
+                    })
+                }
+            },
+            |_| async {
+                Ok(lnurlkit::pay::server::CallbackResponse::Ok {
+                    pr: String::new(),
+                    disposable: false,
+                    success_action: Some(
+                        lnurlkit::pay::server::SuccessAction::encrypt(
+                            String::from("descricao"),
+                            "segredo",
+                            b"oiprazereusouobaites!vamocamigos",
+                        )
+                        .expect("encrypt"),
+                    ),
+                    extra: serde_json::Map::new(),
+                })
+            },
+        )
+        .build();
+
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
+
+    let Ok(lnurlkit::client::Entrypoint::Pay(pr)) =
+        client.entrypoint(&format!("lnurlp://{addr}/lnurlp")).await
+    else {
+        panic!("not pay request");
+    };
+
+    let Ok(lnurlkit::client::Invoice {
+        success_action: Some(sa),
+        ..
+    }) = pr
+        .invoice(&lnurlkit::pay::Amount::Millisatoshis(314), None, None, None)
+        .await
+    else {
+        panic!("no success action");
+    };
+
+    let plaintext = sa
+        .decrypt(b"oiprazereusouobaites!vamocamigos")
+        .expect("decrypt");
+
+    assert_eq!(&plaintext as &str, "segredo");
+}
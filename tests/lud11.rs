@@ -1,13 +1,9 @@
 #[tokio::test]
 async fn test() {
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:0")
-        .await
-        .expect("net");
-
-    let addr = listener.local_addr().expect("addr");
+    let addr = "lnurlkit.test";
 
-    let query_url = format!("http://{addr}/lnurlp");
-    let callback_url = url::Url::parse(&format!("http://{addr}/lnurlp/callback")).expect("url");
+    let query_url = format!("https://{addr}/lnurlp");
+    let callback_url = url::Url::parse(&format!("https://{addr}/lnurlp/callback")).expect("url");
 
     let router = lnurlkit::Server::default()
         .pay_request(
@@ -27,47 +23,40 @@ async fn test() {
                         email: None,
                         currencies: None,
                         payer: None,
+                        extra_metadata: lnurlkit::pay::Metadata::default(),
                     })
                 }
             },
             |req: lnurlkit::pay::server::Callback| async move {
-                Ok(lnurlkit::pay::server::CallbackResponse {
+                Ok(lnurlkit::pay::server::CallbackResponse::Ok {
                     pr: String::new(),
                     disposable: matches!(req.amount, lnurlkit::pay::Amount::Millisatoshis(a) if a % 2 == 0),
                     success_action: None,
+                    extra: serde_json::Map::new(),
                 })
             },
         )
         .build();
 
-    tokio::spawn(async move {
-        axum::serve(listener, router).await.expect("serve");
-    });
-
-    let client = lnurlkit::Client::default();
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
 
-    let lnurl = bech32::encode(
-        "lnurl",
-        bech32::ToBase32::to_base32(&query_url),
-        bech32::Variant::Bech32,
-    )
-    .expect("lnurl");
+    let lnurl = lnurlkit::lnurl::encode(&url::Url::parse(&query_url).expect("url")).expect("lnurl");
 
     let queried = client.entrypoint(&lnurl).await.expect("query");
     let lnurlkit::client::Entrypoint::Pay(pr) = queried else {
         panic!("not pay request");
     };
 
-    let invoice = pr
+    let lnurlkit::client::Invoice { disposable, .. } = pr
         .invoice(&lnurlkit::pay::Amount::Millisatoshis(314), None, None, None)
         .await
         .expect("callback");
 
-    assert!(invoice.disposable);
+    assert!(disposable);
 
-    let invoice = pr
+    let lnurlkit::client::Invoice { disposable, .. } = pr
         .invoice(&lnurlkit::pay::Amount::Millisatoshis(315), None, None, None)
         .await
         .expect("callback");
-    assert!(!invoice.disposable);
+    assert!(!disposable);
 }
@@ -1,13 +1,9 @@
 #[tokio::test]
 async fn test() {
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:0")
-        .await
-        .expect("net");
-
-    let addr = listener.local_addr().expect("addr");
+    let addr = "lnurlkit.test";
 
-    let query_url = format!("http://{addr}/lnurlp");
-    let callback_url = url::Url::parse(&format!("http://{addr}/lnurlp/callback")).expect("url");
+    let query_url = format!("https://{addr}/lnurlp");
+    let callback_url = url::Url::parse(&format!("https://{addr}/lnurlp/callback")).expect("url");
 
     let router = lnurlkit::Server::default()
         .pay_request(
@@ -27,31 +23,24 @@ async fn test() {
                         email: None,
                         currencies: None,
                         payer: None,
+                        extra_metadata: lnurlkit::pay::Metadata::default(),
                     })
                 }
             },
             |req: lnurlkit::pay::server::Callback| async move {
-                Ok(lnurlkit::pay::server::CallbackResponse {
+                Ok(lnurlkit::pay::server::CallbackResponse::Ok {
                     pr: format!("pierre:{:?}", req.comment),
                     disposable: false,
                     success_action: None,
+                    extra: serde_json::Map::new(),
                 })
             },
         )
         .build();
 
-    tokio::spawn(async move {
-        axum::serve(listener, router).await.expect("serve");
-    });
-
-    let client = lnurlkit::Client::default();
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
 
-    let lnurl = bech32::encode(
-        "lnurl",
-        bech32::ToBase32::to_base32(&query_url),
-        bech32::Variant::Bech32,
-    )
-    .expect("lnurl");
+    let lnurl = lnurlkit::lnurl::encode(&url::Url::parse(&query_url).expect("url")).expect("lnurl");
 
     let queried = client.entrypoint(&lnurl).await.expect("query");
     let lnurlkit::client::Entrypoint::Pay(pr) = queried else {
@@ -60,14 +49,14 @@ async fn test() {
 
     assert_eq!(pr.core.comment_size.unwrap(), 140);
 
-    let invoice = pr
+    let lnurlkit::client::Invoice { pr: invoice, .. } = pr
         .invoice(&lnurlkit::pay::Amount::Millisatoshis(314), None, None, None)
         .await
         .expect("callback");
 
-    assert_eq!(&invoice.pr as &str, "pierre:None");
+    assert_eq!(&invoice as &str, "pierre:None");
 
-    let invoice = pr
+    let lnurlkit::client::Invoice { pr: invoice, .. } = pr
         .invoice(
             &lnurlkit::pay::Amount::Millisatoshis(314),
             Some("comentario"),
@@ -77,5 +66,5 @@ async fn test() {
         .await
         .expect("callback");
 
-    assert_eq!(&invoice.pr as &str, "pierre:Some(\"comentario\")");
+    assert_eq!(&invoice as &str, "pierre:Some(\"comentario\")");
 }
@@ -0,0 +1,47 @@
+#[tokio::test]
+async fn test() {
+    let addr = "lnurlkit.test";
+
+    let callback_url = url::Url::parse(&format!("https://{addr}/lnurlw/callback")).expect("url");
+    let balance_url = url::Url::parse(&format!("https://{addr}/lnurlw/balance/caum")).expect("url");
+
+    let entrypoint = move |k1: String, max| lnurlkit::withdraw::server::Entrypoint {
+        description: String::new(),
+        k1,
+        callback: callback_url.clone(),
+        min: 1,
+        max,
+        balance_check: Some(balance_url.clone()),
+        pay_link: None,
+    };
+
+    let router = lnurlkit::Server::default()
+        .withdraw_request(
+            {
+                let entrypoint = entrypoint.clone();
+                move |()| {
+                    let entrypoint = entrypoint(String::from("caum"), 315);
+                    async { Ok(entrypoint) }
+                }
+            },
+            |_| async { unimplemented!() },
+        )
+        .withdraw_balance_check(move |identifier: String| {
+            let entrypoint = entrypoint(identifier, 42);
+            async { Ok(entrypoint) }
+        })
+        .build();
+
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
+
+    let Ok(lnurlkit::client::Entrypoint::Withdraw(wr)) =
+        client.entrypoint(&format!("lnurlw://{addr}/lnurlw")).await
+    else {
+        panic!("not withdraw request");
+    };
+
+    let refreshed = wr.balance_check().await.expect("balance check");
+
+    assert_eq!(&refreshed.core.k1 as &str, "caum");
+    assert_eq!(refreshed.core.max, 42);
+}
@@ -0,0 +1,58 @@
+#[tokio::test]
+async fn test() {
+    let addr = "lnurlkit.test";
+
+    let callback_url = url::Url::parse(&format!("https://{addr}/lnurlw/callback")).expect("url");
+    let notify_url = url::Url::parse("https://wallet.example/notify?id=caum").expect("url");
+
+    let notified = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let router = lnurlkit::Server::default()
+        .withdraw_request(
+            move |()| {
+                let callback = callback_url.clone();
+                async {
+                    Ok(lnurlkit::withdraw::server::Entrypoint {
+                        description: String::new(),
+                        k1: String::from("caum"),
+                        callback,
+                        min: 314,
+                        max: 315,
+                        balance_check: None,
+                        pay_link: None,
+                    })
+                }
+            },
+            |_| async {
+                Ok(lnurlkit::CallbackResponse::Ok {
+                    extra: serde_json::Map::new(),
+                })
+            },
+        )
+        .withdraw_balance_notify({
+            let notified = notified.clone();
+            move |(k1, url): (String, url::Url)| {
+                notified.lock().expect("lock").push((k1, url));
+                async {}
+            }
+        })
+        .build();
+
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
+
+    let Ok(lnurlkit::client::Entrypoint::Withdraw(wr)) =
+        client.entrypoint(&format!("lnurlw://{addr}/lnurlw")).await
+    else {
+        panic!("not withdraw request");
+    };
+
+    wr.submit("pierre", None).await.expect("callback");
+    assert!(notified.lock().expect("lock").is_empty());
+
+    wr.submit("pierre", Some(&notify_url))
+        .await
+        .expect("callback");
+
+    let notified = notified.lock().expect("lock").clone();
+    assert_eq!(notified, vec![(String::from("caum"), notify_url)]);
+}
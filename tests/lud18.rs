@@ -1,13 +1,9 @@
 #[tokio::test]
 async fn test() {
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:0")
-        .await
-        .expect("net");
-
-    let addr = listener.local_addr().expect("addr");
+    let addr = "lnurlkit.test";
 
-    let query_url = format!("http://{addr}/lnurlp");
-    let callback_url = url::Url::parse(&format!("http://{addr}/lnurlp/callback")).expect("url");
+    let query_url = format!("https://{addr}/lnurlp");
+    let callback_url = url::Url::parse(&format!("https://{addr}/lnurlp/callback")).expect("url");
 
     let router = lnurlkit::Server::default()
         .pay_request(
@@ -37,31 +33,24 @@ async fn test() {
                             }),
                             others: std::collections::HashMap::new(),
                         }),
+                        extra_metadata: lnurlkit::pay::Metadata::default(),
                     })
                 }
             },
             |req: lnurlkit::pay::server::Callback| async move {
-                Ok(lnurlkit::pay::server::CallbackResponse {
+                Ok(lnurlkit::pay::server::CallbackResponse::Ok {
                     pr: format!("pierre:{:?}", req.payer),
                     disposable: false,
                     success_action: None,
+                    extra: serde_json::Map::new(),
                 })
             },
         )
         .build();
 
-    tokio::spawn(async move {
-        axum::serve(listener, router).await.expect("serve");
-    });
-
-    let client = lnurlkit::Client::default();
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
 
-    let lnurl = bech32::encode(
-        "lnurl",
-        bech32::ToBase32::to_base32(&query_url),
-        bech32::Variant::Bech32,
-    )
-    .expect("lnurl");
+    let lnurl = lnurlkit::lnurl::encode(&url::Url::parse(&query_url).expect("url")).expect("lnurl");
 
     let queried = client.entrypoint(&lnurl).await.expect("query");
     let lnurlkit::client::Entrypoint::Pay(pr) = queried else {
@@ -81,7 +70,7 @@ async fn test() {
         matches!(payer.identifier.as_ref().unwrap(), lnurlkit::pay::PayerRequirement { mandatory } if *mandatory)
     );
 
-    let invoice = pr
+    let lnurlkit::client::Invoice { pr: invoice, .. } = pr
         .invoice(
             &lnurlkit::pay::Amount::Millisatoshis(314),
             Some("comment"),
@@ -94,12 +83,13 @@ async fn test() {
                 auth: Some(lnurlkit::pay::PayerInformationAuth {
                     key: b"linkinpark".to_vec(),
                     k1: *b"12312312312312312312321312312312",
-                    sig: *b"1231231231231231231232131231231212312312312312312312321312312312",
+                    sig: b"1231231231231231231232131231231212312312312312312312321312312312"
+                        .to_vec(),
                 }),
             }),
         )
         .await
         .expect("callback");
 
-    assert_eq!(&invoice.pr as &str, "pierre:Some(PayerInformations { name: None, pubkey: None, identifier: Some(\"senhor\"), email: None, auth: Some(PayerInformationAuth { key: [108, 105, 110, 107, 105, 110, 112, 97, 114, 107], k1: [49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 50, 49, 51, 49, 50, 51, 49, 50, 51, 49, 50], sig: [49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 50, 49, 51, 49, 50, 51, 49, 50, 51, 49, 50, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 50, 49, 51, 49, 50, 51, 49, 50, 51, 49, 50] }) })");
+    assert_eq!(&invoice as &str, "pierre:Some(PayerInformations { name: None, pubkey: None, identifier: Some(\"senhor\"), email: None, auth: Some(PayerInformationAuth { key: [108, 105, 110, 107, 105, 110, 112, 97, 114, 107], k1: [49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 50, 49, 51, 49, 50, 51, 49, 50, 51, 49, 50], sig: [49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 50, 49, 51, 49, 50, 51, 49, 50, 51, 49, 50, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 49, 50, 51, 50, 49, 51, 49, 50, 51, 49, 50, 51, 49, 50] }) })");
 }
@@ -0,0 +1,65 @@
+#[tokio::test]
+async fn test() {
+    let addr = "lnurlkit.test";
+
+    let withdraw_callback_url =
+        url::Url::parse(&format!("https://{addr}/lnurlw/callback")).expect("url");
+    let pay_callback_url =
+        url::Url::parse(&format!("https://{addr}/lnurlp/callback")).expect("url");
+    let pay_link = format!("lnurlp://{addr}/lnurlp");
+
+    let router = lnurlkit::Server::default()
+        .withdraw_request(
+            move |()| {
+                let callback = withdraw_callback_url.clone();
+                let pay_link = Some(pay_link.clone());
+                async {
+                    Ok(lnurlkit::withdraw::server::Entrypoint {
+                        description: String::new(),
+                        k1: String::from("caum"),
+                        callback,
+                        min: 314,
+                        max: 315,
+                        balance_check: None,
+                        pay_link,
+                    })
+                }
+            },
+            |_| async { unimplemented!() },
+        )
+        .pay_request(
+            move |_| {
+                let callback = pay_callback_url.clone();
+                async {
+                    Ok(lnurlkit::pay::server::Entrypoint {
+                        callback,
+                        short_description: String::from("recarga"),
+                        long_description: None,
+                        jpeg: None,
+                        png: None,
+                        comment_size: None,
+                        min: 1000,
+                        max: 2000,
+                        identifier: None,
+                        email: None,
+                        currencies: None,
+                        payer: None,
+                        extra_metadata: lnurlkit::pay::Metadata::default(),
+                    })
+                }
+            },
+            |_| async { unimplemented!() },
+        )
+        .build();
+
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
+
+    let Ok(lnurlkit::client::Entrypoint::Withdraw(wr)) =
+        client.entrypoint(&format!("lnurlw://{addr}/lnurlw")).await
+    else {
+        panic!("not withdraw request");
+    };
+
+    let pr = wr.pay_link().await.expect("pay link");
+    assert_eq!(&pr.core.short_description as &str, "recarga");
+}
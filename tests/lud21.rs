@@ -1,13 +1,9 @@
 #[tokio::test]
 async fn test() {
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:0")
-        .await
-        .expect("net");
-
-    let addr = listener.local_addr().expect("addr");
+    let addr = "lnurlkit.test";
 
-    let query_url = format!("http://{addr}/lnurlp");
-    let callback_url = url::Url::parse(&format!("http://{addr}/lnurlp/callback")).expect("url");
+    let query_url = format!("https://{addr}/lnurlp");
+    let callback_url = url::Url::parse(&format!("https://{addr}/lnurlp/callback")).expect("url");
 
     let router = lnurlkit::Server::default()
         .pay_request(
@@ -34,7 +30,7 @@ async fn test() {
                                 multiplier: 314.15,
                                 convertible: Some(lnurlkit::pay::CurrencyConvertible {
                                     min: 1,
-                                    max: 3
+                                    max: 3,
                                 }),
                             },
                             lnurlkit::pay::Currency {
@@ -47,31 +43,24 @@ async fn test() {
                             },
                         ]),
                         payer: None,
+                        extra_metadata: lnurlkit::pay::Metadata::default(),
                     })
                 }
             },
             |req: lnurlkit::pay::server::Callback| async move {
-                Ok(lnurlkit::pay::server::CallbackResponse {
+                Ok(lnurlkit::pay::server::CallbackResponse::Ok {
                     pr: format!("pierre:{:?}:{:?}", req.amount, req.convert),
                     disposable: false,
                     success_action: None,
+                    extra: serde_json::Map::new(),
                 })
             },
         )
         .build();
 
-    tokio::spawn(async move {
-        axum::serve(listener, router).await.expect("serve");
-    });
-
-    let client = lnurlkit::Client::default();
+    let client = lnurlkit::Client::new(lnurlkit::client::transport::InMemory::new(router));
 
-    let lnurl = bech32::encode(
-        "lnurl",
-        bech32::ToBase32::to_base32(&query_url),
-        bech32::Variant::Bech32,
-    )
-    .expect("lnurl");
+    let lnurl = lnurlkit::lnurl::encode(&url::Url::parse(&query_url).expect("url")).expect("lnurl");
 
     let queried = client.entrypoint(&lnurl).await.expect("query");
     let lnurlkit::client::Entrypoint::Pay(pr) = queried else {
@@ -97,7 +86,7 @@ async fn test() {
     assert!((currencies[1].multiplier - 123.321).abs() < f64::EPSILON);
     assert!(currencies[1].convertible.is_none());
 
-    let invoice = pr
+    let lnurlkit::client::Invoice { pr: invoice, .. } = pr
         .invoice(
             &lnurlkit::pay::Amount::Currency(String::from("USD"), 314),
             None,
@@ -108,7 +97,7 @@ async fn test() {
         .expect("callback");
 
     assert_eq!(
-        &invoice.pr as &str,
+        &invoice as &str,
         "pierre:Currency(\"USD\", 314):Some(\"BRL\")"
     );
 }